@@ -1,71 +1,633 @@
-use crate::models::{GameweekData, PicksData};
+use crate::cache::{CachedResponse, ResponseCache};
+use crate::constants::{
+    BOOTSTRAP_DATA_URI, DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_MAX_RETRIES,
+    DEFAULT_MIN_REQUEST_INTERVAL_MS, DEFAULT_RETRY_BASE_DELAY_MS, DEFAULT_RETRY_MAX_DELAY_MS,
+    MAX_CONCURRENT_REQUESTS_ENV, MAX_LEAGUE_MEMBERS, MAX_RETRIES_ENV, MIN_REQUEST_INTERVAL_ENV,
+    RETRY_BASE_DELAY_ENV,
+};
+use crate::errors::FplCheckerError;
+use crate::models::{
+    BootstrapData, ElementSummary, GameweekData, H2hMatch, H2hMatchesData, LeagueStandingsData,
+    LeagueType, PicksData, TeamsRequest, TransferData,
+};
+use crate::recorder::SnapshotRecorder;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Semaphore;
+use ureq::http::Response;
+use ureq::{Agent, Body};
 use url::Url;
 
-pub fn fetch_data_as_json<T>(uri: &str) -> Result<T, Box<dyn std::error::Error>>
-where
-    T: for<'de> serde::Deserialize<'de>,
-{
-    // Validate the URL
-    validate_fpl_url(uri)?;
+/// Fetches data from the FPL API, going through the on-disk cache when one is configured, or
+/// from a directory of saved snapshots when running offline. Every payload used can also be
+/// archived by a recorder.
+#[derive(Debug, Clone)]
+pub struct FplClient {
+    cache: Option<ResponseCache>,
+    offline_dir: Option<PathBuf>,
+    recorder: Option<SnapshotRecorder>,
+    max_concurrent_requests: usize,
+    rate_limiter: Arc<RateLimiter>,
+    retry_policy: RetryPolicy,
+    agent: Agent,
+}
+
+/// How many times, and how patiently, to retry requests that fail for transient reasons.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+/// Spaces out requests to the FPL servers, shared by every clone of a client.
+#[derive(Debug)]
+struct RateLimiter {
+    min_interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl Default for FplClient {
+    fn default() -> Self {
+        Self {
+            cache: None,
+            offline_dir: None,
+            recorder: None,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            rate_limiter: Arc::new(RateLimiter::new(Duration::from_millis(
+                DEFAULT_MIN_REQUEST_INTERVAL_MS,
+            ))),
+            retry_policy: RetryPolicy::default(),
+            // Error statuses are handled by `call` so their Retry-After headers can be read
+            agent: Agent::config_builder()
+                .http_status_as_error(false)
+                .build()
+                .new_agent(),
+        }
+    }
+}
+
+impl FplClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(
+        mut self,
+        max_concurrent_requests: usize,
+        min_request_interval: Duration,
+    ) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self.rate_limiter = Arc::new(RateLimiter::new(min_request_interval));
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Applies `max_concurrent_requests` if given, otherwise the request limit environment
+    /// variables, falling back to the defaults.
+    pub fn with_limits_from_env(self, max_concurrent_requests: Option<usize>) -> Self {
+        let max_concurrent_requests = max_concurrent_requests
+            .or_else(|| parse_env(MAX_CONCURRENT_REQUESTS_ENV))
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+        let min_request_interval = Duration::from_millis(
+            parse_env(MIN_REQUEST_INTERVAL_ENV).unwrap_or(DEFAULT_MIN_REQUEST_INTERVAL_MS),
+        );
+
+        self.with_limits(max_concurrent_requests, min_request_interval)
+    }
+
+    pub fn with_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_offline_dir(mut self, offline_dir: Option<&Path>) -> Self {
+        self.offline_dir = offline_dir.map(Path::to_path_buf);
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: Option<SnapshotRecorder>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    /// Returns a client for a single run, so concurrent runs record into separate archives.
+    pub fn start_session(&self) -> Self {
+        Self {
+            recorder: self.recorder.as_ref().map(SnapshotRecorder::start_session),
+            ..self.clone()
+        }
+    }
+
+    /// Files everything recorded in this session under the gameweek being checked.
+    pub fn archive_gameweek(&self, gameweek: i64) -> Result<Option<PathBuf>, FplCheckerError> {
+        self.recorder
+            .as_ref()
+            .map(|recorder| recorder.archive_gameweek(gameweek))
+            .transpose()
+    }
+
+    /// Runs a blocking fetch on Tokio's blocking pool so it doesn't stall the async runtime.
+    pub async fn fetch_blocking<T, F>(&self, fetch: F) -> Result<T, FplCheckerError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&FplClient) -> Result<T, FplCheckerError> + Send + 'static,
+    {
+        let client = self.clone();
+
+        tokio::task::spawn_blocking(move || fetch(&client))
+            .await
+            .map_err(|error| FplCheckerError::Internal(format!("Fetch task failed: {}", error)))?
+    }
+
+    /// Expands a request into the team ids to check: those listed, then any classic or
+    /// head-to-head league members not already listed.
+    pub async fn fetch_team_ids(
+        &self,
+        request: &TeamsRequest,
+    ) -> Result<Vec<i64>, FplCheckerError> {
+        let mut team_ids = request.teams.clone();
+
+        if let Some(league_id) = request.league {
+            let members = self
+                .fetch_blocking(move |client| {
+                    client.fetch_league_members(LeagueType::Classic, &league_id)
+                })
+                .await?;
+            add_missing(&mut team_ids, members);
+        }
+        if let Some(league_id) = request.h2h_league {
+            let members = self
+                .fetch_blocking(move |client| {
+                    client.fetch_league_members(LeagueType::HeadToHead, &league_id)
+                })
+                .await?;
+            add_missing(&mut team_ids, members);
+        }
+
+        Ok(team_ids)
+    }
+
+    /// Fetches every item with at most `max_concurrent_requests` in flight, returning results
+    /// in the same order as `items`.
+    pub async fn fetch_all<I, T, F>(
+        &self,
+        items: Vec<I>,
+        fetch: F,
+    ) -> Vec<Result<T, FplCheckerError>>
+    where
+        I: Send + 'static,
+        T: Send + 'static,
+        F: Fn(&FplClient, I) -> Result<T, FplCheckerError> + Send + Sync + 'static,
+    {
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_requests));
+        let fetch = Arc::new(fetch);
+
+        let handles: Vec<_> = items
+            .into_iter()
+            .map(|item| {
+                let client = self.clone();
+                let semaphore = semaphore.clone();
+                let fetch = fetch.clone();
+
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire_owned().await.map_err(|error| {
+                        FplCheckerError::Internal(format!("Fetch queue closed: {}", error))
+                    })?;
+                    client
+                        .fetch_blocking(move |client| fetch(client, item))
+                        .await
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            results.push(handle.await.unwrap_or_else(|error| {
+                Err(FplCheckerError::Internal(format!(
+                    "Fetch task failed: {}",
+                    error
+                )))
+            }));
+        }
+
+        results
+    }
+
+    pub fn fetch_data_as_json<T>(&self, uri: &str) -> Result<T, FplCheckerError>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        // Validate the URL
+        validate_fpl_url(uri)?;
+
+        let body = self.fetch_body(uri)?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(uri, &body)?;
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub fn fetch_bootstrap(&self) -> Result<BootstrapData, FplCheckerError> {
+        self.fetch_data_as_json(BOOTSTRAP_DATA_URI)
+    }
+
+    pub fn fetch_picks(&self, team_id: &i64, gameweek: i64) -> Result<PicksData, FplCheckerError> {
+        // Validate inputs
+        validate_team_id(team_id)?;
+        validate_gameweek(gameweek)?;
+
+        self.fetch_data_as_json(&format!(
+            "https://fantasy.premierleague.com/api/entry/{}/event/{}/picks/",
+            team_id, gameweek
+        ))
+    }
+
+    pub fn fetch_league_standings_page(
+        &self,
+        league_type: LeagueType,
+        league_id: &i64,
+        page: i64,
+    ) -> Result<LeagueStandingsData, FplCheckerError> {
+        validate_league_id(league_id)?;
+
+        self.fetch_data_as_json(&format!(
+            "https://fantasy.premierleague.com/api/{}/{}/standings/?page_standings={}",
+            league_type.path(),
+            league_id,
+            page
+        ))
+    }
+
+    /// Walks every page of a league's standings and returns its entry ids in rank order. Leagues
+    /// with more than `MAX_LEAGUE_MEMBERS` members, such as Overall, are refused rather than
+    /// crawled.
+    pub fn fetch_league_members(
+        &self,
+        league_type: LeagueType,
+        league_id: &i64,
+    ) -> Result<Vec<i64>, FplCheckerError> {
+        let mut members = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let standings = self
+                .fetch_league_standings_page(league_type, league_id, page)?
+                .standings;
+            members.extend(standings.results.iter().map(|standing| standing.entry));
+
+            if members.len() > MAX_LEAGUE_MEMBERS {
+                return Err(FplCheckerError::LeagueTooLarge(*league_id));
+            }
+            if !standings.has_next {
+                return Ok(members);
+            }
+            page += 1;
+        }
+    }
+
+    /// Fetches every head-to-head fixture a league played in `gameweek`, across all pages.
+    pub fn fetch_h2h_matches(
+        &self,
+        league_id: &i64,
+        gameweek: i64,
+    ) -> Result<Vec<H2hMatch>, FplCheckerError> {
+        validate_league_id(league_id)?;
+        validate_gameweek(gameweek)?;
+        let mut matches = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let matches_page: H2hMatchesData = self.fetch_data_as_json(&format!(
+                "https://fantasy.premierleague.com/api/leagues-h2h-matches/league/{}/?event={}&page={}",
+                league_id, gameweek, page
+            ))?;
+            matches.extend(matches_page.results);
+
+            if !matches_page.has_next {
+                return Ok(matches);
+            }
+            page += 1;
+        }
+    }
+
+    pub fn fetch_transfers(&self, team_id: &i64) -> Result<Vec<TransferData>, FplCheckerError> {
+        validate_team_id(team_id)?;
+
+        self.fetch_data_as_json(&transfers_uri(team_id))
+    }
+
+    /// Fetches the manager's transfers, treating a manager without any (or an offline snapshot
+    /// recorded without them) as `None` so players keep their current price. Any other failure,
+    /// such as a cache that can't be written, is still an error.
+    pub fn fetch_transfers_if_available(
+        &self,
+        team_id: &i64,
+    ) -> Result<Option<Vec<TransferData>>, FplCheckerError> {
+        validate_team_id(team_id)?;
+
+        if let Some(offline_dir) = &self.offline_dir
+            && !offline_dir
+                .join(relative_path_for_uri(&transfers_uri(team_id))?)
+                .exists()
+        {
+            return Ok(None);
+        }
+
+        match self.fetch_transfers(team_id) {
+            Ok(transfers) => Ok(Some(transfers)),
+            Err(FplCheckerError::HttpStatus(404)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn fetch_element_summary(
+        &self,
+        player_id: &i64,
+    ) -> Result<ElementSummary, FplCheckerError> {
+        if *player_id <= 0 {
+            return Err(FplCheckerError::UnknownPlayer(*player_id));
+        }
+
+        self.fetch_data_as_json(&format!(
+            "https://fantasy.premierleague.com/api/element-summary/{}/",
+            player_id
+        ))
+    }
+
+    pub fn fetch_gameweek_data(&self, team_id: &i64) -> Result<GameweekData, FplCheckerError> {
+        // Validate team ID
+        validate_team_id(team_id)?;
+
+        self.fetch_data_as_json(&format!(
+            "https://fantasy.premierleague.com/api/entry/{}/",
+            team_id
+        ))
+    }
+
+    fn fetch_body(&self, uri: &str) -> Result<String, FplCheckerError> {
+        if let Some(offline_dir) = &self.offline_dir {
+            return read_snapshot(offline_dir, uri);
+        }
+
+        let Some(cache) = &self.cache else {
+            return Ok(self.call(uri, None)?.into_body().read_to_string()?);
+        };
+
+        let cached = cache.lookup(uri);
+        if let Some(cached) = &cached
+            && cached.is_fresh
+        {
+            return Ok(cached.body.clone());
+        }
+
+        let response = self.call(uri, cached.as_ref())?;
+
+        if response.status() == 304
+            && let Some(cached) = cached
+        {
+            cache.touch(uri, &cached)?;
+            return Ok(cached.body);
+        }
+
+        let etag = header_value(&response, "etag");
+        let last_modified = header_value(&response, "last-modified");
+        let body = response.into_body().read_to_string()?;
+        cache.store(uri, &body, etag, last_modified)?;
+
+        Ok(body)
+    }
+
+    /// Sends a GET, revalidating `cached` if given, and retries transient failures with
+    /// jittered exponential backoff, waiting as long as `Retry-After` asks where it's given.
+    fn call(
+        &self,
+        uri: &str,
+        cached: Option<&CachedResponse>,
+    ) -> Result<Response<Body>, FplCheckerError> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.agent.get(uri);
+            if let Some(cached) = cached {
+                if let Some(etag) = &cached.metadata.etag {
+                    request = request.header("If-None-Match", etag);
+                }
+                if let Some(last_modified) = &cached.metadata.last_modified {
+                    request = request.header("If-Modified-Since", last_modified);
+                }
+            }
+
+            self.rate_limiter.wait();
+            let (error, retry_after) = match request.call() {
+                Ok(response) if response.status().as_u16() < 400 => return Ok(response),
+                Ok(response) => (
+                    FplCheckerError::HttpStatus(response.status().as_u16()),
+                    header_value(&response, "retry-after")
+                        .and_then(|value| parse_retry_after(&value, SystemTime::now())),
+                ),
+                Err(error) => (FplCheckerError::from(error), None),
+            };
+
+            if !error.is_retryable() || attempt >= self.retry_policy.max_retries {
+                return Err(error);
+            }
+
+            let delay = retry_after
+                .unwrap_or_else(|| self.retry_policy.backoff(attempt))
+                .min(self.retry_policy.max_delay);
+            eprintln!(
+                "{} fetching {}, retrying in {}ms",
+                error,
+                uri,
+                delay.as_millis()
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+fn add_missing(team_ids: &mut Vec<i64>, members: Vec<i64>) {
+    for member in members {
+        if !team_ids.contains(&member) {
+            team_ids.push(member);
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: Duration::from_millis(DEFAULT_RETRY_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_RETRY_MAX_DELAY_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Reads the retry environment variables, falling back to the defaults.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_retries: parse_env(MAX_RETRIES_ENV).unwrap_or(defaults.max_retries),
+            base_delay: parse_env(RETRY_BASE_DELAY_ENV)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            max_delay: defaults.max_delay,
+        }
+    }
+
+    /// Picks a random delay up to `base_delay * 2^attempt`, capped at `max_delay`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        Duration::from_millis(rand::random_range(0..=ceiling.as_millis() as u64))
+    }
+}
+
+impl RateLimiter {
+    fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Blocks until this caller's slot comes round, reserving the following one.
+    fn wait(&self) {
+        let now = Instant::now();
+        let slot = match self.next_slot.lock() {
+            Ok(mut next_slot) => {
+                let slot = (*next_slot).max(now);
+                *next_slot = slot + self.min_interval;
+                slot
+            }
+            Err(_) => now,
+        };
 
-    let data = ureq::get(uri).call()?.into_body().read_json::<T>()?;
-    Ok(data)
+        std::thread::sleep(slot.saturating_duration_since(now));
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
 }
 
-pub fn fetch_picks(team_id: &i64, gameweek_data: &GameweekData) -> PicksData {
-    // Validate inputs
-    validate_team_id(team_id).expect("Invalid team ID");
-    validate_gameweek(gameweek_data.current_event).expect("Invalid gameweek");
+/// Parses a `Retry-After` header given either as a number of seconds or as an HTTP date.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
 
-    let picks_data: PicksData = fetch_data_as_json(&format!(
-        "https://fantasy.premierleague.com/api/entry/{}/event/{}/picks/",
-        team_id, gameweek_data.current_event
-    ))
-        .expect("Something went wrong fetching picks data");
-    picks_data
+    httpdate::parse_http_date(value.trim())
+        .ok()
+        .map(|retry_at| retry_at.duration_since(now).unwrap_or_default())
 }
 
-pub fn fetch_gameweek_data(team_id: &i64) -> GameweekData {
-    // Validate team ID
-    validate_team_id(team_id).expect("Invalid team ID");
+/// Maps an FPL API URL onto a relative file path, e.g. `/api/entry/1/event/2/picks/` becomes
+/// `entry/1/event/2/picks.json`, so cached and saved payloads share one directory layout.
+pub fn relative_path_for_uri(uri: &str) -> Result<PathBuf, FplCheckerError> {
+    let url = Url::parse(uri)?;
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .filter(|segment| !segment.is_empty() && *segment != "api")
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if segments.is_empty() || segments.iter().any(|segment| segment.starts_with('.')) {
+        return Err(FplCheckerError::InvalidUrl(format!(
+            "Cannot map {} to a file",
+            uri
+        )));
+    }
+
+    let mut path: PathBuf = segments.iter().collect();
+    let mut file_name = segments[segments.len() - 1].to_string();
+    if let Some(query) = url.query() {
+        file_name.push('-');
+        file_name.push_str(&query.replace(['&', '='], "-"));
+    }
+    path.set_file_name(format!("{}.json", file_name));
+
+    Ok(path)
+}
 
-    let gameweek_data: GameweekData = fetch_data_as_json(&format!(
-        "https://fantasy.premierleague.com/api/entry/{}/",
+fn transfers_uri(team_id: &i64) -> String {
+    format!(
+        "https://fantasy.premierleague.com/api/entry/{}/transfers/",
         team_id
-    ))
-        .expect("Something went wrong fetching gameweek data");
-    gameweek_data
+    )
 }
 
-fn validate_team_id(team_id: &i64) -> Result<(), String> {
+fn read_snapshot(directory: &Path, uri: &str) -> Result<String, FplCheckerError> {
+    let path = directory.join(relative_path_for_uri(uri)?);
+
+    std::fs::read_to_string(&path).map_err(|error| {
+        FplCheckerError::Io(format!("Could not read {}: {}", path.display(), error))
+    })
+}
+
+fn header_value(response: &Response<Body>, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+pub fn validate_team_id(team_id: &i64) -> Result<(), FplCheckerError> {
     if *team_id <= 0 || *team_id > 100_000_000 {
-        return Err(format!(
-            "Invalid team ID: must be between 1 and 100,000,000, got {}",
-            team_id
-        ));
+        return Err(FplCheckerError::InvalidTeamId(*team_id));
+    }
+    Ok(())
+}
+
+pub fn validate_league_id(league_id: &i64) -> Result<(), FplCheckerError> {
+    if *league_id <= 0 || *league_id > 100_000_000 {
+        return Err(FplCheckerError::InvalidLeagueId(*league_id));
     }
     Ok(())
 }
 
-fn validate_gameweek(gameweek: i64) -> Result<(), String> {
+pub fn validate_gameweek(gameweek: i64) -> Result<(), FplCheckerError> {
     if gameweek <= 0 || gameweek > 100 {
-        return Err(format!(
-            "Invalid gameweek: must be between 1 and 100, got {}",
-            gameweek
-        ));
+        return Err(FplCheckerError::InvalidGameweek(gameweek));
     }
     Ok(())
 }
 
-fn validate_fpl_url(url_str: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn validate_fpl_url(url_str: &str) -> Result<(), FplCheckerError> {
     let url = Url::parse(url_str)?;
 
     if url.host_str() != Some("fantasy.premierleague.com") {
-        return Err(format!("Invalid host: {:?}", url.host_str()).into());
+        return Err(FplCheckerError::InvalidUrl(format!(
+            "Invalid host: {:?}",
+            url.host_str()
+        )));
     }
 
     if url.scheme() != "https" {
-        return Err(format!("Must use HTTPS, got: {}", url.scheme()).into());
+        return Err(FplCheckerError::InvalidUrl(format!(
+            "Must use HTTPS, got: {}",
+            url.scheme()
+        )));
     }
 
     Ok(())
@@ -74,19 +636,25 @@ fn validate_fpl_url(url_str: &str) -> Result<(), Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod security_tests {
     use super::*;
+    use crate::test_support::{temp_path, write_snapshots};
 
     #[test]
-    #[should_panic(expected = "Invalid team ID")]
     fn should_reject_negative_team_id() {
         let malicious_team_id = -1;
-        fetch_gameweek_data(&malicious_team_id);
+        let result = FplClient::new().fetch_gameweek_data(&malicious_team_id);
+
+        assert!(matches!(result, Err(FplCheckerError::InvalidTeamId(-1))));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid team ID")]
     fn should_reject_unreasonably_large_team_id() {
         let malicious_team_id = 999_999_999;
-        fetch_gameweek_data(&malicious_team_id);
+        let result = FplClient::new().fetch_gameweek_data(&malicious_team_id);
+
+        assert!(matches!(
+            result,
+            Err(FplCheckerError::InvalidTeamId(999_999_999))
+        ));
     }
 
     #[test]
@@ -94,7 +662,7 @@ mod security_tests {
         // This would construct:
         // "https://fantasy.premierleague.com/api/entry/1@evil.com/x86/"
         // Some HTTP clients might interpret this as a request to evil.com
-        let result = fetch_data_as_json::<GameweekData>(
+        let result = FplClient::new().fetch_data_as_json::<GameweekData>(
             "https://fantasy.premierleague.com/api/entry/1@evil.com/x86/",
         );
 
@@ -106,30 +674,32 @@ mod security_tests {
 
     #[test]
     fn should_reject_url_with_different_host() {
-        let result = fetch_data_as_json::<GameweekData>("https://evil.com/malware");
+        let result =
+            FplClient::new().fetch_data_as_json::<GameweekData>("https://evil.com/malware");
 
         assert!(result.is_err(), "Should reject non-FPL host");
     }
 
     #[test]
     fn should_reject_non_https_scheme() {
-        let result =
-            fetch_data_as_json::<GameweekData>("http://fantasy.premierleague.com/api/entry/1/");
+        let result = FplClient::new()
+            .fetch_data_as_json::<GameweekData>("http://fantasy.premierleague.com/api/entry/1/");
 
         assert!(result.is_err(), "Should reject HTTP (non-HTTPS) URLs");
     }
 
     #[test]
     fn should_reject_file_scheme() {
-        let result = fetch_data_as_json::<GameweekData>("file:///etc/passwd");
+        let result = FplClient::new().fetch_data_as_json::<GameweekData>("file:///etc/passwd");
 
         assert!(result.is_err(), "Should reject file:// URLs");
     }
 
     #[test]
-    #[should_panic(expected = "Invalid team ID")]
     fn should_reject_zero_team_id() {
-        fetch_gameweek_data(&0);
+        let result = FplClient::new().fetch_gameweek_data(&0);
+
+        assert!(matches!(result, Err(FplCheckerError::InvalidTeamId(0))));
     }
 
     #[test]
@@ -142,9 +712,166 @@ mod security_tests {
         assert!(result.is_ok(), "Should accept valid team ID");
     }
 
+    #[test]
+    fn should_reject_out_of_range_gameweek() {
+        let result = validate_gameweek(0);
+
+        assert!(matches!(result, Err(FplCheckerError::InvalidGameweek(0))));
+    }
+
+    #[test]
+    fn should_map_urls_to_snapshot_paths() {
+        assert_eq!(
+            relative_path_for_uri(BOOTSTRAP_DATA_URI).unwrap(),
+            PathBuf::from("bootstrap-static.json")
+        );
+        assert_eq!(
+            relative_path_for_uri("https://fantasy.premierleague.com/api/entry/123/").unwrap(),
+            PathBuf::from("entry/123.json")
+        );
+        assert_eq!(
+            relative_path_for_uri("https://fantasy.premierleague.com/api/entry/123/event/4/picks/")
+                .unwrap(),
+            PathBuf::from("entry/123/event/4/picks.json")
+        );
+    }
+
+    #[test]
+    fn should_not_map_urls_escaping_the_snapshot_directory() {
+        let result =
+            relative_path_for_uri("https://fantasy.premierleague.com/api/entry/..%2F..%2Fetc/");
+
+        assert!(result.is_err(), "Should reject dot segments");
+    }
+
+    #[tokio::test]
+    async fn should_return_concurrent_fetches_in_input_order() {
+        let client = FplClient::new().with_limits(3, Duration::ZERO);
+
+        let results = client
+            .fetch_all(vec![30u64, 0, 20, 10], |_, delay| {
+                std::thread::sleep(Duration::from_millis(delay));
+                Ok(delay)
+            })
+            .await;
+        let actual: Vec<u64> = results.into_iter().map(|result| result.unwrap()).collect();
+
+        assert_eq!(actual, vec![30, 0, 20, 10]);
+    }
+
+    #[test]
+    fn should_space_out_requests_to_the_same_host() {
+        let rate_limiter = RateLimiter::new(Duration::from_millis(20));
+        let started = Instant::now();
+
+        rate_limiter.wait();
+        rate_limiter.wait();
+        rate_limiter.wait();
+
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn should_parse_retry_after_in_seconds_and_as_http_date() {
+        let now = httpdate::parse_http_date("Sat, 16 Aug 2025 10:00:00 GMT").unwrap();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Sat, 16 Aug 2025 10:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn should_cap_jittered_backoff() {
+        let retry_policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(250),
+        };
+
+        for attempt in 0..5 {
+            assert!(retry_policy.backoff(attempt) <= Duration::from_millis(250));
+        }
+        assert!(retry_policy.backoff(0) <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn should_only_retry_transient_failures() {
+        assert!(FplCheckerError::HttpStatus(503).is_retryable());
+        assert!(FplCheckerError::HttpStatus(429).is_retryable());
+        assert!(FplCheckerError::Network("connection reset".to_string()).is_retryable());
+        assert!(!FplCheckerError::HttpStatus(404).is_retryable());
+        assert!(!FplCheckerError::Decode("expected value".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn should_only_treat_missing_transfer_snapshots_as_no_transfers() {
+        let offline_dir = temp_path("transfers");
+        let _ = std::fs::remove_dir_all(&offline_dir);
+        // A directory where the snapshot belongs, so reading it fails rather than finding nothing
+        std::fs::create_dir_all(offline_dir.join("entry/1/transfers.json"))
+            .expect("Something went wrong creating snapshot directory");
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+
+        let missing = client.fetch_transfers_if_available(&2);
+        let unreadable = client.fetch_transfers_if_available(&1);
+
+        assert!(matches!(missing, Ok(None)));
+        assert!(matches!(unreadable, Err(FplCheckerError::Io(_))));
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[test]
+    fn should_reject_out_of_range_league_id() {
+        let result = FplClient::new().fetch_league_members(LeagueType::Classic, &0);
+
+        assert!(matches!(result, Err(FplCheckerError::InvalidLeagueId(0))));
+    }
+
+    #[test]
+    fn should_refuse_leagues_too_large_to_check() {
+        // Every page claims there's another, as a public league's would for thousands of pages
+        let pages: Vec<(String, String)> = (1..=MAX_LEAGUE_MEMBERS / 50 + 1)
+            .map(|page| {
+                let results: Vec<String> = (0..50)
+                    .map(|index| format!("{{\"entry\": {}}}", page * 50 + index))
+                    .collect();
+                (
+                    format!("leagues-classic/314/standings-page_standings-{}.json", page),
+                    format!(
+                        "{{\"standings\": {{\"has_next\": true, \"results\": [{}]}}}}",
+                        results.join(",")
+                    ),
+                )
+            })
+            .collect();
+        let offline_dir = write_snapshots(
+            "large_league",
+            &pages
+                .iter()
+                .map(|(path, contents)| (path.as_str(), contents.as_str()))
+                .collect::<Vec<_>>(),
+        );
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+
+        let result = client.fetch_league_members(LeagueType::Classic, &314);
+
+        assert!(matches!(result, Err(FplCheckerError::LeagueTooLarge(314))));
+        assert_eq!(
+            FplCheckerError::LeagueTooLarge(314).status(),
+            rocket::http::Status::BadRequest
+        );
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
     #[test]
     fn should_accept_valid_url() {
         let result = validate_fpl_url("https://fantasy.premierleague.com/api/entry/123/");
         assert!(result.is_ok(), "Should accept valid FPL URL");
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,10 @@
-use crate::models::{BootstrapData, Club, GameweekData, PicksData, Player, Team};
+use crate::api::validate_gameweek;
+use crate::errors::FplCheckerError;
+use crate::models::{
+    Availability, BootstrapData, Club, ElementSummary, GameweekData, GameweekTransfers, PicksData,
+    PlayLimits, Player, PlayerStatus, Position, Team, Transfer, TransferData,
+};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
 pub fn build_team_from_data(
@@ -6,36 +12,61 @@ pub fn build_team_from_data(
     players_by_player_id: &HashMap<i64, Player>,
     gameweek_data: &GameweekData,
     picks_data: &PicksData,
-) -> Team {
+) -> Result<Team, FplCheckerError> {
     let mut players = Vec::new();
     let mut captain = Player::default();
+    let mut vice_captain = None;
 
     for pick in &picks_data.picks {
         let id = pick.element;
         let player = Player {
-            id,
-            name: players_by_player_id.get(&id).unwrap().name.clone(),
-            price_in_millions: players_by_player_id.get(&id).unwrap().price_in_millions,
-            club: Club {
-                id: players_by_player_id.get(&id).unwrap().club.id,
-                name: players_by_player_id.get(&id).unwrap().club.name.to_string(),
-            },
+            lineup_slot: Some(pick.position),
+            multiplier: Some(pick.multiplier),
+            ..players_by_player_id
+                .get(&id)
+                .ok_or(FplCheckerError::UnknownPlayer(id))?
+                .clone()
         };
 
         if pick.is_captain {
             captain = player.clone();
         }
+        if pick.is_vice_captain {
+            vice_captain = Some(player.clone());
+        }
 
         players.push(player);
     }
 
-    Team {
+    Ok(Team {
         id: team_id,
         name: gameweek_data.name.clone(),
         owner: gameweek_data.player_first_name.clone(),
+        points: picks_data.entry_history.points,
+        active_chip: picks_data.active_chip,
         captain,
+        vice_captain,
+        previous_captain_id: None,
+        gameweek_transfers: GameweekTransfers {
+            count: picks_data.entry_history.event_transfers,
+            points_cost: picks_data.entry_history.event_transfers_cost,
+            transfers: Vec::new(),
+        },
+        bank_in_millions: picks_data.entry_history.bank.map(|bank| bank as f64 / 10.0),
+        value_in_millions: picks_data
+            .entry_history
+            .value
+            .map(|value| value as f64 / 10.0),
         players,
-    }
+    })
+}
+
+pub fn find_captain_id(picks_data: &PicksData) -> Option<i64> {
+    picks_data
+        .picks
+        .iter()
+        .find(|pick| pick.is_captain)
+        .map(|pick| pick.element)
 }
 
 pub fn build_clubs_by_id(bootstrap_data: &BootstrapData) -> HashMap<i64, Club> {
@@ -54,40 +85,246 @@ pub fn build_clubs_by_id(bootstrap_data: &BootstrapData) -> HashMap<i64, Club> {
     clubs_by_id
 }
 
+/// How many of each position a starting XI may field, in the order bootstrap lists them.
+pub fn build_play_limits(bootstrap_data: &BootstrapData) -> IndexMap<Position, PlayLimits> {
+    bootstrap_data
+        .element_types
+        .iter()
+        .filter_map(|element_type| {
+            Position::from_short_name(&element_type.singular_name_short).map(|position| {
+                (
+                    position,
+                    PlayLimits {
+                        min: element_type.squad_min_play,
+                        max: element_type.squad_max_play,
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
 pub fn build_players_by_id(
     clubs_by_club_id: &HashMap<i64, Club>,
     bootstrap_data: &BootstrapData,
-) -> HashMap<i64, Player> {
+) -> Result<HashMap<i64, Player>, FplCheckerError> {
     let mut players_by_id: HashMap<i64, Player> = HashMap::new();
+    let positions_by_element_type: HashMap<i64, Position> = bootstrap_data
+        .element_types
+        .iter()
+        .filter_map(|element_type| {
+            Position::from_short_name(&element_type.singular_name_short)
+                .map(|position| (element_type.id, position))
+        })
+        .collect();
 
     for element in &bootstrap_data.elements {
         let player = Player {
             id: element.id,
             name: element.web_name.clone(),
             price_in_millions: element.now_cost / 10.0,
-            club: Club {
-                id: element.team,
-                name: match clubs_by_club_id.get(&element.team) {
-                    Some(team_name) => team_name.name.clone(),
-                    _ => {
-                        panic!("Could not find a team")
-                    }
-                },
-            },
+            club: clubs_by_club_id
+                .get(&element.team)
+                .ok_or(FplCheckerError::UnknownClub(element.team))?
+                .clone(),
+            position: positions_by_element_type
+                .get(&element.element_type)
+                .copied(),
+            availability: (element.status != PlayerStatus::Available).then(|| Availability {
+                status: element.status,
+                news: element.news.clone(),
+                chance_of_playing_next_round: element.chance_of_playing_next_round,
+            }),
+            ..Player::default()
         };
 
         players_by_id.insert(element.id, player);
     }
 
-    players_by_id
+    Ok(players_by_id)
 }
 
-pub fn get_current_gameweek(bootstrap_data: &BootstrapData) -> i64 {
+/// Uses the requested gameweek if one was given, otherwise the current one.
+pub fn resolve_gameweek(
+    bootstrap_data: &BootstrapData,
+    requested_gameweek: Option<i64>,
+) -> Result<i64, FplCheckerError> {
+    match requested_gameweek {
+        Some(gameweek) => {
+            validate_gameweek(gameweek)?;
+            Ok(gameweek)
+        }
+        None => get_current_gameweek(bootstrap_data),
+    }
+}
+
+/// What each player cost at the start of the season, in millions.
+pub fn build_start_prices(bootstrap_data: &BootstrapData) -> HashMap<i64, f64> {
+    bootstrap_data
+        .elements
+        .iter()
+        .map(|element| {
+            (
+                element.id,
+                (element.now_cost - element.cost_change_start) / 10.0,
+            )
+        })
+        .collect()
+}
+
+/// What a manager who joined in `started_event` paid for their first squad, in millions: season
+/// start prices for a manager there from the first gameweek, otherwise each player's price in
+/// that gameweek from `summaries_by_id`. Players without history for it are left out rather than
+/// given a price the manager never paid.
+pub fn build_first_squad_prices(
+    start_prices: &HashMap<i64, f64>,
+    summaries_by_id: &HashMap<i64, ElementSummary>,
+    started_event: i64,
+) -> HashMap<i64, f64> {
+    if started_event <= 1 {
+        return start_prices.clone();
+    }
+
+    summaries_by_id
+        .iter()
+        .filter_map(|(id, summary)| {
+            let price = summary
+                .history
+                .iter()
+                .rev()
+                .find(|history| history.round <= started_event)?;
+            Some((*id, price.value / 10.0))
+        })
+        .collect()
+}
+
+/// The players in `team` the manager hasn't transferred in by `gameweek`, so still from their
+/// first squad.
+pub fn first_squad_player_ids(team: &Team, transfers: &[TransferData], gameweek: i64) -> Vec<i64> {
+    team.players
+        .iter()
+        .map(|player| player.id)
+        .filter(|id| {
+            !transfers
+                .iter()
+                .any(|transfer| transfer.element_in == *id && transfer.event <= gameweek)
+        })
+        .collect()
+}
+
+/// Fills in what the manager paid for each player as of `gameweek` and what they'd sell them for.
+/// Players never transferred in are priced from `first_squad_prices` (see
+/// [`build_first_squad_prices`]), and selling prices keep half of any rise, rounded down to
+/// 0.1m, as FPL does.
+pub fn apply_transfer_prices(
+    mut team: Team,
+    transfers: &[TransferData],
+    first_squad_prices: &HashMap<i64, f64>,
+    gameweek: i64,
+) -> Team {
+    let apply = |player: &mut Player| {
+        // Transfers are listed newest first
+        let purchase_price = transfers
+            .iter()
+            .find(|transfer| transfer.element_in == player.id && transfer.event <= gameweek)
+            .map(|transfer| transfer.element_in_cost / 10.0)
+            .or_else(|| first_squad_prices.get(&player.id).copied());
+
+        if let Some(purchase_price) = purchase_price {
+            let purchase_tenths = (purchase_price * 10.0).round() as i64;
+            let current_tenths = (player.price_in_millions * 10.0).round() as i64;
+            let selling_tenths = if current_tenths > purchase_tenths {
+                purchase_tenths + (current_tenths - purchase_tenths) / 2
+            } else {
+                current_tenths
+            };
+
+            player.purchase_price_in_millions = Some(purchase_price);
+            player.selling_price_in_millions = Some(selling_tenths as f64 / 10.0);
+        }
+    };
+
+    team.players.iter_mut().for_each(apply);
+    apply(&mut team.captain);
+    team.vice_captain.iter_mut().for_each(apply);
+
+    team
+}
+
+/// Fills in the transfers the manager made going into `gameweek`, pricing each player at what
+/// the transfer cost.
+pub fn apply_gameweek_transfers(
+    mut team: Team,
+    transfers: &[TransferData],
+    players_by_player_id: &HashMap<i64, Player>,
+    gameweek: i64,
+) -> Result<Team, FplCheckerError> {
+    let find_player = |id: i64| {
+        players_by_player_id
+            .get(&id)
+            .cloned()
+            .ok_or(FplCheckerError::UnknownPlayer(id))
+    };
+
+    team.gameweek_transfers.transfers = transfers
+        .iter()
+        .filter(|transfer| transfer.event == gameweek)
+        .map(|transfer| {
+            Ok(Transfer {
+                player_in: Player {
+                    purchase_price_in_millions: Some(transfer.element_in_cost / 10.0),
+                    ..find_player(transfer.element_in)?
+                },
+                player_out: Player {
+                    selling_price_in_millions: Some(transfer.element_out_cost / 10.0),
+                    ..find_player(transfer.element_out)?
+                },
+            })
+        })
+        .collect::<Result<Vec<Transfer>, FplCheckerError>>()?;
+
+    Ok(team)
+}
+
+pub fn get_current_gameweek(bootstrap_data: &BootstrapData) -> Result<i64, FplCheckerError> {
     for event in &bootstrap_data.events {
         if event.is_current {
-            return event.id;
+            return Ok(event.id);
+        }
+    }
+
+    Err(FplCheckerError::NoCurrentGameweek)
+}
+
+pub fn get_finished_gameweeks(bootstrap_data: &BootstrapData) -> Vec<i64> {
+    bootstrap_data
+        .events
+        .iter()
+        .filter(|event| event.finished)
+        .map(|event| event.id)
+        .collect()
+}
+
+/// Reprices players to what they cost in `gameweek`. Blank gameweeks fall back to the latest
+/// earlier price, and players without any history keep their current price.
+pub fn build_players_at_gameweek(
+    players_by_id: &HashMap<i64, Player>,
+    summaries_by_id: &HashMap<i64, ElementSummary>,
+    gameweek: i64,
+) -> HashMap<i64, Player> {
+    let mut players_at_gameweek = players_by_id.clone();
+
+    for (id, summary) in summaries_by_id {
+        let price = summary
+            .history
+            .iter()
+            .rev()
+            .find(|history| history.round <= gameweek);
+
+        if let (Some(player), Some(price)) = (players_at_gameweek.get_mut(id), price) {
+            player.price_in_millions = price.value / 10.0;
         }
     }
 
-    panic!("Cannot determine current gameweek");
+    players_at_gameweek
 }
@@ -0,0 +1,245 @@
+use crate::api::relative_path_for_uri;
+use crate::constants::{
+    BOOTSTRAP_DATA_URI, BOOTSTRAP_TTL_ENV, CACHE_DIR_ENV, DEFAULT_BOOTSTRAP_TTL_SECS,
+    DEFAULT_ENTRY_TTL_SECS, ENTRY_TTL_ENV,
+};
+use crate::errors::FplCheckerError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Stores raw FPL responses on disk alongside the validators needed to revalidate them.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    directory: PathBuf,
+    bootstrap_ttl: Duration,
+    entry_ttl: Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct CacheMetadata {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: u64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CachedResponse {
+    pub body: String,
+    pub metadata: CacheMetadata,
+    pub is_fresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(directory: &Path, bootstrap_ttl: Duration, entry_ttl: Duration) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            bootstrap_ttl,
+            entry_ttl,
+        }
+    }
+
+    /// Builds a cache from `directory` if one was given, otherwise from the cache environment
+    /// variables. Returns `None` when caching hasn't been configured.
+    pub fn from_env(directory: Option<&str>) -> Option<Self> {
+        let directory = match directory {
+            Some(directory) => directory.to_string(),
+            None => std::env::var(CACHE_DIR_ENV).ok()?,
+        };
+
+        Some(Self::new(
+            Path::new(&directory),
+            ttl_from_env(BOOTSTRAP_TTL_ENV, DEFAULT_BOOTSTRAP_TTL_SECS),
+            ttl_from_env(ENTRY_TTL_ENV, DEFAULT_ENTRY_TTL_SECS),
+        ))
+    }
+
+    pub fn lookup(&self, uri: &str) -> Option<CachedResponse> {
+        let (body_path, metadata_path) = self.paths_for(uri).ok()?;
+        let body = std::fs::read_to_string(body_path).ok()?;
+        let metadata: CacheMetadata =
+            serde_json::from_str(&std::fs::read_to_string(metadata_path).ok()?).ok()?;
+        let age = now().saturating_sub(metadata.fetched_at);
+
+        Some(CachedResponse {
+            body,
+            is_fresh: age < self.ttl_for(uri).as_secs(),
+            metadata,
+        })
+    }
+
+    pub fn store(
+        &self,
+        uri: &str,
+        body: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Result<(), FplCheckerError> {
+        let (body_path, metadata_path) = self.paths_for(uri)?;
+        let metadata = CacheMetadata {
+            etag,
+            last_modified,
+            fetched_at: now(),
+        };
+
+        write_file(&body_path, body)?;
+        write_file(&metadata_path, &serde_json::to_string(&metadata)?)
+    }
+
+    /// Marks a cached response as fresh again after the server confirmed it hasn't changed.
+    pub fn touch(&self, uri: &str, cached: &CachedResponse) -> Result<(), FplCheckerError> {
+        let (_, metadata_path) = self.paths_for(uri)?;
+        let metadata = CacheMetadata {
+            fetched_at: now(),
+            ..cached.metadata.clone()
+        };
+
+        write_file(&metadata_path, &serde_json::to_string(&metadata)?)
+    }
+
+    fn ttl_for(&self, uri: &str) -> Duration {
+        if uri == BOOTSTRAP_DATA_URI {
+            self.bootstrap_ttl
+        } else {
+            self.entry_ttl
+        }
+    }
+
+    fn paths_for(&self, uri: &str) -> Result<(PathBuf, PathBuf), FplCheckerError> {
+        let body_path = self.directory.join(relative_path_for_uri(uri)?);
+        let metadata_path = body_path.with_extension("meta.json");
+
+        Ok((body_path, metadata_path))
+    }
+}
+
+/// Writes to a temporary file beside `path` and renames it into place, so a concurrent reader
+/// sees either the old contents or the new ones, never a partial write.
+pub fn write_file(path: &Path, contents: &str) -> Result<(), FplCheckerError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .ok_or_else(|| FplCheckerError::Io(format!("not a file path: {}", path.display())))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(&temp_path, path).map_err(|error| {
+        let _ = std::fs::remove_file(&temp_path);
+        FplCheckerError::from(error)
+    })
+}
+
+fn ttl_from_env(name: &str, default_secs: u64) -> Duration {
+    Duration::from_secs(
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default_secs),
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_path;
+    use assertor::*;
+
+    const ENTRY_URI: &str = "https://fantasy.premierleague.com/api/entry/123/";
+
+    fn cache_in_temp_dir(name: &str, entry_ttl: Duration) -> ResponseCache {
+        let directory = temp_path(&format!("cache_{}", name));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        ResponseCache::new(&directory, Duration::from_secs(60), entry_ttl)
+    }
+
+    #[test]
+    fn should_return_stored_response_as_fresh_within_ttl() {
+        let cache = cache_in_temp_dir("fresh", Duration::from_secs(60));
+        cache
+            .store(ENTRY_URI, "{\"id\":123}", Some("\"abc\"".to_string()), None)
+            .expect("Something went wrong storing response");
+
+        let actual = cache.lookup(ENTRY_URI).expect("Response not cached");
+
+        assert_that!(actual.body).is_equal_to("{\"id\":123}".to_string());
+        assert_that!(actual.metadata.etag).is_equal_to(Some("\"abc\"".to_string()));
+        assert_that!(actual.is_fresh).is_true();
+
+        let _ = std::fs::remove_dir_all(&cache.directory);
+    }
+
+    #[test]
+    fn should_keep_expired_response_for_revalidation() {
+        let cache = cache_in_temp_dir("expired", Duration::ZERO);
+        cache
+            .store(
+                ENTRY_URI,
+                "{\"id\":123}",
+                None,
+                Some("Sat, 16 Aug 2025 10:00:00 GMT".to_string()),
+            )
+            .expect("Something went wrong storing response");
+
+        let actual = cache.lookup(ENTRY_URI).expect("Response not cached");
+
+        assert_that!(actual.is_fresh).is_false();
+        assert_that!(actual.metadata.last_modified)
+            .is_equal_to(Some("Sat, 16 Aug 2025 10:00:00 GMT".to_string()));
+
+        let _ = std::fs::remove_dir_all(&cache.directory);
+    }
+
+    #[test]
+    fn should_never_read_a_partially_written_response() {
+        let cache = cache_in_temp_dir("concurrent", Duration::from_secs(60));
+        let bodies = ["a".repeat(1 << 20), "b".repeat(1 << 20)];
+        cache
+            .store(ENTRY_URI, &bodies[0], None, None)
+            .expect("Something went wrong storing response");
+
+        std::thread::scope(|scope| {
+            for body in &bodies {
+                let cache = &cache;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        cache
+                            .store(ENTRY_URI, body, None, None)
+                            .expect("Something went wrong storing response");
+                    }
+                });
+            }
+            for _ in 0..50 {
+                let actual = cache.lookup(ENTRY_URI).expect("Response not cached");
+                assert_that!(bodies.contains(&actual.body)).is_true();
+            }
+        });
+
+        let _ = std::fs::remove_dir_all(&cache.directory);
+    }
+
+    #[test]
+    fn should_miss_for_uncached_uri() {
+        let cache = cache_in_temp_dir("miss", Duration::from_secs(60));
+
+        assert_that!(cache.lookup(ENTRY_URI)).is_equal_to(None);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::api::FplClient;
+use crate::builders::{self, build_team_from_data};
+use crate::errors::FplCheckerError;
+use crate::history::ViolationHistory;
+use crate::models::{
+    BootstrapData, Club, PlayLimits, Player, Position, Team, TeamOutcome, TeamsRequest,
+};
+use crate::rules::{RuleContext, RuleRegistry};
+use crate::validators;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Everything a check builds from a single bootstrap fetch.
+pub struct BootstrapLookups {
+    pub bootstrap_data: BootstrapData,
+    pub clubs_by_club_id: HashMap<i64, Club>,
+    pub players_by_id: Arc<HashMap<i64, Player>>,
+    pub start_prices: Arc<HashMap<i64, f64>>,
+    pub play_limits_by_position: IndexMap<Position, PlayLimits>,
+}
+
+impl BootstrapLookups {
+    pub async fn fetch(client: &FplClient) -> Result<Self, FplCheckerError> {
+        let bootstrap_data: BootstrapData = client
+            .fetch_blocking(|client| client.fetch_bootstrap())
+            .await?;
+        let clubs_by_club_id = builders::build_clubs_by_id(&bootstrap_data);
+        let players_by_id = builders::build_players_by_id(&clubs_by_club_id, &bootstrap_data)?;
+
+        Ok(Self {
+            start_prices: Arc::new(builders::build_start_prices(&bootstrap_data)),
+            play_limits_by_position: builders::build_play_limits(&bootstrap_data),
+            players_by_id: Arc::new(players_by_id),
+            clubs_by_club_id,
+            bootstrap_data,
+        })
+    }
+
+    pub fn context(&self) -> RuleContext<'_> {
+        RuleContext {
+            clubs_by_club_id: &self.clubs_by_club_id,
+            play_limits_by_position: &self.play_limits_by_position,
+        }
+    }
+}
+
+pub async fn run_validation_for_teams(
+    request: TeamsRequest,
+    registry: &RuleRegistry,
+    client: &FplClient,
+    history: Option<&ViolationHistory>,
+    source: &str,
+) -> Result<Vec<TeamOutcome>, FplCheckerError> {
+    let (_, outcomes) =
+        check_gameweek(request, registry, &client.start_session(), history, source).await?;
+    Ok(outcomes)
+}
+
+/// Checks every requested team in a session that's already been started, returning the gameweek
+/// checked alongside the outcomes. Violations are kept in `history` if given. Picks are only
+/// health checked for the current gameweek or one still to come.
+pub async fn check_gameweek(
+    request: TeamsRequest,
+    registry: &RuleRegistry,
+    client: &FplClient,
+    history: Option<&ViolationHistory>,
+    source: &str,
+) -> Result<(i64, Vec<TeamOutcome>), FplCheckerError> {
+    let team_ids = client.fetch_team_ids(&request).await?;
+    eprintln!("Checking teams {:?} from {}", team_ids, source);
+    let lookups = BootstrapLookups::fetch(client).await?;
+    let gameweek = builders::resolve_gameweek(&lookups.bootstrap_data, request.gameweek)?;
+    let context = lookups.context();
+    // Availability is only known for now, so it means nothing for a past gameweek. Before the
+    // season starts there's no current gameweek and every gameweek is still to come.
+    let is_current_or_upcoming = builders::get_current_gameweek(&lookups.bootstrap_data)
+        .ok()
+        .is_none_or(|current_gameweek| gameweek >= current_gameweek);
+
+    eprintln!("Checking gameweek {}...", gameweek);
+    if let Some(archive_dir) = client.archive_gameweek(gameweek)? {
+        eprintln!("Recording FPL data to {}", archive_dir.display());
+    }
+
+    let needs_previous_captain = registry.needs_previous_captain();
+    let needs_transfers = registry.needs_transfers();
+    let players_by_id = lookups.players_by_id.clone();
+    let start_prices = lookups.start_prices.clone();
+
+    let teams = client
+        .fetch_all(team_ids.clone(), move |client, fpl_team_id| {
+            fetch_team(
+                client,
+                fpl_team_id,
+                gameweek,
+                &players_by_id,
+                &start_prices,
+                needs_previous_captain,
+                needs_transfers,
+            )
+        })
+        .await;
+    let mut outcomes: Vec<TeamOutcome> = Vec::new();
+
+    for (fpl_team_id, team) in team_ids.into_iter().zip(teams) {
+        let outcome = match team {
+            Ok(team) => {
+                let mut report = validators::run_validators(registry, &context, &team);
+                if is_current_or_upcoming {
+                    report.health_check = team.health_check();
+                }
+                TeamOutcome::Checked(Box::new(report))
+            }
+            Err(error) => {
+                eprintln!("Could not check team {}: {}", fpl_team_id, error);
+                TeamOutcome::Failed {
+                    team_id: fpl_team_id,
+                    error,
+                }
+            }
+        };
+
+        outcomes.push(outcome);
+    }
+
+    if let Some(history) = history {
+        history.record(outcomes.iter().map(|outcome| (gameweek, outcome)))?;
+    }
+
+    Ok((gameweek, outcomes))
+}
+
+fn fetch_team(
+    client: &FplClient,
+    fpl_team_id: i64,
+    gameweek: i64,
+    players_by_id: &HashMap<i64, Player>,
+    start_prices: &HashMap<i64, f64>,
+    needs_previous_captain: bool,
+    needs_transfers: bool,
+) -> Result<Team, FplCheckerError> {
+    let gameweek_data = client.fetch_gameweek_data(&fpl_team_id)?;
+    let picks_data = client.fetch_picks(&fpl_team_id, gameweek)?;
+    let mut team = build_team_from_data(fpl_team_id, players_by_id, &gameweek_data, &picks_data)?;
+
+    if needs_previous_captain && gameweek > 1 {
+        // A manager who joined this gameweek has no previous picks, so nobody to repeat.
+        team.previous_captain_id = match client.fetch_picks(&fpl_team_id, gameweek - 1) {
+            Ok(previous_picks) => builders::find_captain_id(&previous_picks),
+            Err(FplCheckerError::HttpStatus(404)) => None,
+            Err(error) => return Err(error),
+        };
+    }
+
+    if !needs_transfers {
+        return Ok(team);
+    }
+    match client.fetch_transfers_if_available(&fpl_team_id)? {
+        Some(transfers) => {
+            let mut summaries_by_id = HashMap::new();
+            if gameweek_data.started_event > 1 {
+                // A late joiner bought their first squad at that gameweek's prices
+                for player_id in builders::first_squad_player_ids(&team, &transfers, gameweek) {
+                    summaries_by_id.insert(player_id, client.fetch_element_summary(&player_id)?);
+                }
+            }
+            let first_squad_prices = builders::build_first_squad_prices(
+                start_prices,
+                &summaries_by_id,
+                gameweek_data.started_event,
+            );
+            let team =
+                builders::apply_transfer_prices(team, &transfers, &first_squad_prices, gameweek);
+            builders::apply_gameweek_transfers(team, &transfers, players_by_id, gameweek)
+        }
+        None => Ok(team),
+    }
+}
+
+pub fn outcomes_by_team_id(outcomes: Vec<TeamOutcome>) -> IndexMap<i64, TeamOutcome> {
+    outcomes
+        .into_iter()
+        .map(|outcome| (outcome.team_id(), outcome))
+        .collect()
+}
@@ -1,5 +1,28 @@
 pub const NEWLY_PROMOTED_CLUBS: [i64; 3] = [3, 11, 17];
+pub const DEFAULT_PRICE_LIMIT_IN_MILLIONS: f64 = 10.0;
+pub const DEFAULT_MAX_PLAYERS_PER_CLUB: usize = 1;
+pub const RULEBOOK_PATH_ENV: &str = "FPL_CHECKER_RULEBOOK";
 pub const BOOTSTRAP_DATA_URI: &str = "https://fantasy.premierleague.com/api/bootstrap-static/";
+pub const CACHE_DIR_ENV: &str = "FPL_CHECKER_CACHE_DIR";
+pub const BOOTSTRAP_TTL_ENV: &str = "FPL_CHECKER_BOOTSTRAP_TTL_SECS";
+pub const ENTRY_TTL_ENV: &str = "FPL_CHECKER_ENTRY_TTL_SECS";
+pub const DEFAULT_BOOTSTRAP_TTL_SECS: u64 = 60 * 60;
+pub const DEFAULT_ENTRY_TTL_SECS: u64 = 5 * 60;
+pub const MAX_CONCURRENT_REQUESTS_ENV: &str = "FPL_CHECKER_MAX_CONCURRENT_REQUESTS";
+pub const MIN_REQUEST_INTERVAL_ENV: &str = "FPL_CHECKER_MIN_REQUEST_INTERVAL_MS";
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+pub const DEFAULT_MIN_REQUEST_INTERVAL_MS: u64 = 100;
+pub const MAX_RETRIES_ENV: &str = "FPL_CHECKER_MAX_RETRIES";
+pub const RETRY_BASE_DELAY_ENV: &str = "FPL_CHECKER_RETRY_BASE_DELAY_MS";
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 500;
+pub const DEFAULT_RETRY_MAX_DELAY_MS: u64 = 60_000;
+pub const POINTS_PER_HIT: i64 = 4;
+pub const DEFAULT_MAX_HITS: i64 = 1;
+pub const DEFAULT_SQUAD_VALUE_LIMIT_IN_MILLIONS: f64 = 100.0;
+pub const DEFAULT_MIN_BANK_IN_MILLIONS: f64 = 1.0;
+pub const MAX_LEAGUE_MEMBERS: usize = 1000;
+pub const HISTORY_DB_ENV: &str = "FPL_CHECKER_HISTORY_DB";
 pub const VIOLATION_PREFIXES: [&str; 25] = [
     "Yikes!",
     "Womp womp,",
@@ -0,0 +1,182 @@
+use crate::constants::MAX_LEAGUE_MEMBERS;
+use rocket::Request;
+use rocket::http::{ContentType, Status};
+use rocket::response::{self, Responder, Response};
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::io::Cursor;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum FplCheckerError {
+    Network(String),
+    HttpStatus(u16),
+    InvalidTeamId(i64),
+    InvalidGameweek(i64),
+    InvalidLeagueId(i64),
+    LeagueTooLarge(i64),
+    InvalidUrl(String),
+    UnknownPlayer(i64),
+    UnknownClub(i64),
+    NoCurrentGameweek,
+    Decode(String),
+    Rulebook(String),
+    Io(String),
+    Storage(String),
+    Internal(String),
+}
+
+impl FplCheckerError {
+    pub fn status(&self) -> Status {
+        match self {
+            FplCheckerError::InvalidTeamId(_)
+            | FplCheckerError::InvalidGameweek(_)
+            | FplCheckerError::InvalidLeagueId(_)
+            | FplCheckerError::LeagueTooLarge(_)
+            | FplCheckerError::InvalidUrl(_) => Status::BadRequest,
+            FplCheckerError::HttpStatus(404) => Status::NotFound,
+            FplCheckerError::NoCurrentGameweek => Status::ServiceUnavailable,
+            FplCheckerError::Network(_)
+            | FplCheckerError::HttpStatus(_)
+            | FplCheckerError::UnknownPlayer(_)
+            | FplCheckerError::UnknownClub(_)
+            | FplCheckerError::Decode(_) => Status::BadGateway,
+            FplCheckerError::Rulebook(_)
+            | FplCheckerError::Io(_)
+            | FplCheckerError::Storage(_)
+            | FplCheckerError::Internal(_) => Status::InternalServerError,
+        }
+    }
+
+    /// Whether the request is worth retrying: throttling, server errors and dropped connections.
+    /// Anything else, such as a 404 for an entry that doesn't exist, fails straight away.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FplCheckerError::HttpStatus(status) => *status == 429 || *status >= 500,
+            FplCheckerError::Network(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FplCheckerError::InvalidTeamId(_)
+            | FplCheckerError::InvalidGameweek(_)
+            | FplCheckerError::InvalidLeagueId(_)
+            | FplCheckerError::LeagueTooLarge(_)
+            | FplCheckerError::InvalidUrl(_)
+            | FplCheckerError::Rulebook(_) => 2,
+            FplCheckerError::Network(_) | FplCheckerError::HttpStatus(_) => 3,
+            FplCheckerError::UnknownPlayer(_)
+            | FplCheckerError::UnknownClub(_)
+            | FplCheckerError::Decode(_) => 4,
+            FplCheckerError::NoCurrentGameweek => 5,
+            FplCheckerError::Io(_) | FplCheckerError::Storage(_) | FplCheckerError::Internal(_) => {
+                1
+            }
+        }
+    }
+}
+
+impl Display for FplCheckerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FplCheckerError::Network(reason) => {
+                write!(f, "Could not reach the FPL API: {}", reason)
+            }
+            FplCheckerError::HttpStatus(status) => {
+                write!(f, "FPL API responded with status {}", status)
+            }
+            FplCheckerError::InvalidTeamId(team_id) => write!(
+                f,
+                "Invalid team ID: must be between 1 and 100,000,000, got {}",
+                team_id
+            ),
+            FplCheckerError::InvalidGameweek(gameweek) => write!(
+                f,
+                "Invalid gameweek: must be between 1 and 100, got {}",
+                gameweek
+            ),
+            FplCheckerError::InvalidLeagueId(league_id) => write!(
+                f,
+                "Invalid league ID: must be between 1 and 100,000,000, got {}",
+                league_id
+            ),
+            FplCheckerError::LeagueTooLarge(league_id) => write!(
+                f,
+                "League {} has more than {} members, too many to check",
+                league_id, MAX_LEAGUE_MEMBERS
+            ),
+            FplCheckerError::InvalidUrl(reason) => write!(f, "Invalid URL: {}", reason),
+            FplCheckerError::UnknownPlayer(player_id) => {
+                write!(f, "Could not find a player with ID {}", player_id)
+            }
+            FplCheckerError::UnknownClub(club_id) => {
+                write!(f, "Could not find a club with ID {}", club_id)
+            }
+            FplCheckerError::NoCurrentGameweek => write!(f, "Cannot determine current gameweek"),
+            FplCheckerError::Decode(reason) => {
+                write!(f, "Could not decode FPL API response: {}", reason)
+            }
+            FplCheckerError::Rulebook(reason) => write!(f, "Could not load rulebook: {}", reason),
+            FplCheckerError::Io(reason) => write!(f, "Could not access local data: {}", reason),
+            FplCheckerError::Storage(reason) => {
+                write!(f, "Could not access violation history: {}", reason)
+            }
+            FplCheckerError::Internal(reason) => write!(f, "Internal error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FplCheckerError {}
+
+impl From<ureq::Error> for FplCheckerError {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::StatusCode(status) => FplCheckerError::HttpStatus(status),
+            ureq::Error::Json(error) => FplCheckerError::Decode(error.to_string()),
+            error => FplCheckerError::Network(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for FplCheckerError {
+    fn from(error: serde_json::Error) -> Self {
+        FplCheckerError::Decode(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for FplCheckerError {
+    fn from(error: std::io::Error) -> Self {
+        FplCheckerError::Io(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for FplCheckerError {
+    fn from(error: rusqlite::Error) -> Self {
+        FplCheckerError::Storage(error.to_string())
+    }
+}
+
+impl From<url::ParseError> for FplCheckerError {
+    fn from(error: url::ParseError) -> Self {
+        FplCheckerError::InvalidUrl(error.to_string())
+    }
+}
+
+impl Serialize for FplCheckerError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for FplCheckerError {
+    fn respond_to(self, _request: &'r Request<'_>) -> response::Result<'static> {
+        let body = serde_json::json!({ "error": self }).to_string();
+
+        Response::build()
+            .status(self.status())
+            .header(ContentType::JSON)
+            .sized_body(body.len(), Cursor::new(body))
+            .ok()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::api::FplClient;
+use crate::checker;
+use crate::errors::FplCheckerError;
+use crate::history::ViolationHistory;
+use crate::models::{H2hCheck, H2hFixture, Severity, TeamOutcome, TeamsRequest};
+use crate::rules::RuleRegistry;
+
+/// Checks every member of a head-to-head league for a gameweek (the current one by default) and
+/// flags the fixtures that were won by a team breaking the rules.
+pub async fn run_h2h_check(
+    league_id: i64,
+    gameweek: Option<i64>,
+    registry: &RuleRegistry,
+    client: &FplClient,
+    history: Option<&ViolationHistory>,
+    source: &str,
+) -> Result<H2hCheck, FplCheckerError> {
+    let client = client.start_session();
+    let request = TeamsRequest {
+        h2h_league: Some(league_id),
+        gameweek,
+        ..TeamsRequest::default()
+    };
+    let (gameweek, outcomes) =
+        checker::check_gameweek(request, registry, &client, history, source).await?;
+    let teams = checker::outcomes_by_team_id(outcomes);
+    let matches = client
+        .fetch_blocking(move |client| client.fetch_h2h_matches(&league_id, gameweek))
+        .await?;
+
+    let fixtures = matches
+        .into_iter()
+        .map(|fixture| {
+            let winner_broken_rules = match fixture.winner.and_then(|winner| teams.get(&winner)) {
+                // A rule kept as a warning doesn't make the team illegal
+                Some(TeamOutcome::Checked(report)) => report
+                    .violations()
+                    .iter()
+                    .filter(|violation| violation.severity == Severity::Violation)
+                    .map(|violation| violation.rule_id.clone())
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            H2hFixture {
+                entry_1: fixture.entry_1_entry,
+                entry_2: fixture.entry_2_entry,
+                winner: fixture.winner,
+                winner_broken_rules,
+            }
+        })
+        .collect();
+
+    Ok(H2hCheck {
+        gameweek,
+        teams,
+        fixtures,
+    })
+}
+
+/// Lists the wins achieved with an illegal team, so the league admin knows which to overturn.
+pub fn process_h2h_check(check: &H2hCheck) -> String {
+    let team_name = |team_id: Option<i64>| match team_id {
+        None => "the league average".to_string(),
+        Some(team_id) => match check.teams.get(&team_id) {
+            Some(TeamOutcome::Checked(report)) => {
+                format!("{} ({})", report.team.name, report.team.owner)
+            }
+            _ => format!("Team {}", team_id),
+        },
+    };
+    let mut output = format!("== Head-to-head gameweek {} ==\n", check.gameweek);
+
+    let tainted_wins: Vec<&H2hFixture> = check
+        .fixtures
+        .iter()
+        .filter(|fixture| !fixture.winner_broken_rules.is_empty())
+        .collect();
+    if tainted_wins.is_empty() {
+        output.push_str("No wins achieved with an illegal team\n");
+    }
+
+    for fixture in tainted_wins {
+        let loser = if fixture.winner == Some(fixture.entry_1) {
+            fixture.entry_2
+        } else {
+            Some(fixture.entry_1)
+        };
+        output.push_str(&format!(
+            "{} beat {} with an illegal team: {}\n",
+            team_name(fixture.winner),
+            team_name(loser),
+            fixture.winner_broken_rules.join(", ")
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rulebook::Rulebook;
+    use crate::test_support::write_snapshots;
+    use assertor::*;
+
+    const BOOTSTRAP_JSON: &str = include_str!("../tests/samples/bootstrap.json");
+    const GAMEWEEK_JSON: &str = include_str!("../tests/samples/gameweek.json");
+    const PICKS_JSON: &str = include_str!("../tests/samples/picks.json");
+
+    #[tokio::test]
+    async fn should_flag_wins_achieved_with_an_illegal_team() {
+        let offline_dir = write_snapshots(
+            "h2h",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/1/picks.json", PICKS_JSON),
+                (
+                    "leagues-h2h/42/standings-page_standings-1.json",
+                    r#"{"standings": {"has_next": false, "results": [{"entry": 2239760}, {"entry": 5}, {"entry": 7}]}}"#,
+                ),
+                (
+                    "leagues-h2h-matches/league/42-event-1-page-1.json",
+                    r#"{"has_next": false, "results": [
+                    {"entry_1_entry": 2239760, "entry_2_entry": 5, "winner": 2239760},
+                    {"entry_1_entry": 7, "entry_2_entry": null, "winner": null}
+                ]}"#,
+                ),
+            ],
+        );
+        let mut rulebook = Rulebook::default();
+        rulebook.price_limit.price_limit_in_millions = 9.0;
+
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let check = run_h2h_check(
+            42,
+            None,
+            &RuleRegistry::from_rulebook(&rulebook),
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong checking head-to-head league");
+
+        assert_that!(check.gameweek).is_equal_to(1);
+        assert_that!(check.teams.keys().copied().collect::<Vec<i64>>())
+            .is_equal_to(vec![2239760, 5, 7]);
+        assert_that!(
+            check.fixtures[0]
+                .winner_broken_rules
+                .contains(&"price_limit".to_string())
+        )
+        .is_true();
+        assert_that!(check.fixtures[1].winner_broken_rules.is_empty()).is_true();
+        assert_that!(
+            process_h2h_check(&check).contains("beat Team 5 with an illegal team: price_limit")
+        )
+        .is_true();
+
+        rulebook.price_limit.severity = Severity::Warning;
+        let warned = run_h2h_check(
+            42,
+            None,
+            &RuleRegistry::from_rulebook(&rulebook),
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong checking head-to-head league");
+
+        assert_that!(warned.fixtures[0].winner_broken_rules.is_empty()).is_true();
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+}
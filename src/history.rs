@@ -0,0 +1,434 @@
+use crate::constants::HISTORY_DB_ENV;
+use crate::errors::FplCheckerError;
+use crate::models::{Severity, TeamOutcome, TeamReport, ValidationResult, ViolationRecord};
+use indexmap::IndexMap;
+use rusqlite::{Connection, Row, Transaction, params};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS violations (
+        dedup_key TEXT PRIMARY KEY,
+        gameweek INTEGER NOT NULL,
+        team_id INTEGER NOT NULL,
+        team_name TEXT NOT NULL,
+        owner TEXT NOT NULL,
+        rule_id TEXT NOT NULL,
+        severity TEXT NOT NULL,
+        offending_players TEXT NOT NULL,
+        penalty_points INTEGER NOT NULL,
+        message TEXT NOT NULL,
+        recorded_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS violations_by_team ON violations (team_id, gameweek);
+    CREATE INDEX IF NOT EXISTS violations_by_gameweek ON violations (gameweek, team_id);
+";
+
+/// Keeps every rule broken in a local SQLite file, so there's a record of who broke what when
+/// across runs.
+#[derive(Debug, Clone)]
+pub struct ViolationHistory {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl ViolationHistory {
+    pub fn open(path: &Path) -> Result<Self, FplCheckerError> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Opens the history at `path`, falling back to `FPL_CHECKER_HISTORY_DB`. Nothing is recorded
+    /// when neither is set.
+    pub fn from_env(path: Option<&str>) -> Result<Option<Self>, FplCheckerError> {
+        let path = match path {
+            Some(path) => path.to_string(),
+            None => match std::env::var(HISTORY_DB_ENV) {
+                Ok(path) => path,
+                Err(_) => return Ok(None),
+            },
+        };
+
+        Self::open(Path::new(&path)).map(Some)
+    }
+
+    /// Records the violations in each checked team's report for its gameweek. Re-checking a
+    /// gameweek replaces that team's record for it rather than adding to it, and violations
+    /// seen before keep the time they were first recorded.
+    pub fn record<'a>(
+        &self,
+        outcomes: impl IntoIterator<Item = (i64, &'a TeamOutcome)>,
+    ) -> Result<(), FplCheckerError> {
+        let recorded_at = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|error| FplCheckerError::Internal(error.to_string()))?;
+        let mut connection = self.lock_connection()?;
+        let transaction = connection.transaction()?;
+
+        for (gameweek, outcome) in outcomes {
+            if let TeamOutcome::Checked(report) = outcome {
+                record_report(&transaction, gameweek, report, &recorded_at)?;
+            }
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    /// Looks up recorded violations, optionally for a single team and/or gameweek, ordered by
+    /// team then gameweek.
+    pub fn violations(
+        &self,
+        team_id: Option<i64>,
+        gameweek: Option<i64>,
+    ) -> Result<Vec<ViolationRecord>, FplCheckerError> {
+        let connection = self.lock_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT gameweek, team_id, team_name, owner, rule_id, severity, offending_players,
+                    penalty_points, message, recorded_at
+             FROM violations
+             WHERE (?1 IS NULL OR team_id = ?1) AND (?2 IS NULL OR gameweek = ?2)
+             ORDER BY team_id, gameweek, rule_id, dedup_key",
+        )?;
+        let rows = statement.query_map(params![team_id, gameweek], read_record)?;
+
+        Ok(rows.collect::<Result<Vec<ViolationRecord>, rusqlite::Error>>()?)
+    }
+
+    fn lock_connection(&self) -> Result<MutexGuard<'_, Connection>, FplCheckerError> {
+        self.connection
+            .lock()
+            .map_err(|_| FplCheckerError::Storage("Violation history is unavailable".to_string()))
+    }
+}
+
+fn record_report(
+    transaction: &Transaction,
+    gameweek: i64,
+    report: &TeamReport,
+    recorded_at: &str,
+) -> Result<(), FplCheckerError> {
+    let violations = report.violations();
+    let dedup_keys: Vec<String> = violations
+        .iter()
+        .map(|violation| dedup_key(gameweek, violation))
+        .collect();
+
+    transaction.execute(
+        "DELETE FROM violations
+         WHERE gameweek = ?1 AND team_id = ?2
+           AND dedup_key NOT IN (SELECT value FROM json_each(?3))",
+        params![
+            gameweek,
+            report.team.id,
+            serde_json::to_string(&dedup_keys)?
+        ],
+    )?;
+    for (violation, dedup_key) in violations.into_iter().zip(dedup_keys) {
+        let offending_players: Vec<&str> = violation
+            .offending_players
+            .iter()
+            .map(|player| player.name.as_str())
+            .collect();
+
+        transaction.execute(
+            "INSERT INTO violations (dedup_key, gameweek, team_id, team_name, owner, rule_id,
+                                     severity, offending_players, penalty_points, message,
+                                     recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT (dedup_key) DO UPDATE SET
+                 team_name = excluded.team_name,
+                 owner = excluded.owner,
+                 severity = excluded.severity,
+                 offending_players = excluded.offending_players,
+                 penalty_points = excluded.penalty_points,
+                 message = excluded.message",
+            params![
+                dedup_key,
+                gameweek,
+                report.team.id,
+                report.team.name,
+                report.team.owner,
+                violation.rule_id,
+                severity_name(violation.severity),
+                serde_json::to_string(&offending_players)?,
+                violation.penalty_points,
+                violation.message,
+                recorded_at,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Identifies a violation by what was broken rather than how it was worded, since messages are
+/// prefixed at random.
+fn dedup_key(gameweek: i64, violation: &ValidationResult) -> String {
+    let join_ids = |ids: Vec<i64>| {
+        ids.iter()
+            .map(i64::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+
+    format!(
+        "{}:{}:{}:players={}:clubs={}",
+        gameweek,
+        violation.team_id,
+        violation.rule_id,
+        join_ids(
+            violation
+                .offending_players
+                .iter()
+                .map(|player| player.id)
+                .collect()
+        ),
+        join_ids(
+            violation
+                .offending_clubs
+                .iter()
+                .map(|club| club.id)
+                .collect()
+        )
+    )
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Violation => "violation",
+    }
+}
+
+fn read_record(row: &Row) -> Result<ViolationRecord, rusqlite::Error> {
+    let offending_players: String = row.get(6)?;
+    let severity: String = row.get(5)?;
+
+    Ok(ViolationRecord {
+        gameweek: row.get(0)?,
+        team_id: row.get(1)?,
+        team_name: row.get(2)?,
+        owner: row.get(3)?,
+        rule_id: row.get(4)?,
+        severity: match severity.as_str() {
+            "warning" => Severity::Warning,
+            _ => Severity::Violation,
+        },
+        offending_players: serde_json::from_str(&offending_players).map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, error.into())
+        })?,
+        penalty_points: row.get(7)?,
+        message: row.get(8)?,
+        recorded_at: row.get(9)?,
+    })
+}
+
+pub fn records_by_team_id(records: Vec<ViolationRecord>) -> IndexMap<i64, Vec<ViolationRecord>> {
+    let mut records_by_team_id: IndexMap<i64, Vec<ViolationRecord>> = IndexMap::new();
+    for record in records {
+        records_by_team_id
+            .entry(record.team_id)
+            .or_default()
+            .push(record);
+    }
+
+    records_by_team_id
+}
+
+/// Prints a section per manager with a line per recorded violation.
+pub fn process_violation_history(records: Vec<ViolationRecord>) -> String {
+    if records.is_empty() {
+        return "No violations recorded".to_string();
+    }
+
+    let mut output = String::new();
+    for (_, records) in records_by_team_id(records) {
+        output.push_str(&format!(
+            "== {} ({}) ==\n",
+            records[0].team_name, records[0].owner
+        ));
+        for record in &records {
+            output.push_str(&format!(
+                "GW{} {}: {}\n",
+                record.gameweek, record.rule_id, record.message
+            ));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Club, GameweekTransfers, Player, Team};
+    use crate::test_support::temp_path;
+    use assertor::*;
+
+    /// Opens a fresh history in the temp directory, returning its path so the test can remove it.
+    fn history(name: &str) -> (ViolationHistory, std::path::PathBuf) {
+        let path = temp_path(&format!("history_{}", name)).with_extension("db");
+        let _ = std::fs::remove_file(&path);
+        let history = ViolationHistory::open(&path).expect("Something went wrong opening history");
+
+        (history, path)
+    }
+
+    fn report(team_id: i64, offending_player_ids: &[i64]) -> TeamOutcome {
+        let club = Club {
+            id: 1,
+            name: "Arsenal".to_string(),
+        };
+        let players: Vec<Player> = offending_player_ids
+            .iter()
+            .map(|id| Player {
+                id: *id,
+                name: format!("Player {}", id),
+                price_in_millions: 12.0,
+                club: club.clone(),
+                ..Player::default()
+            })
+            .collect();
+        let team = Team {
+            id: team_id,
+            name: format!("Team {}", team_id),
+            owner: format!("Owner {}", team_id),
+            points: 0,
+            active_chip: None,
+            captain: players[0].clone(),
+            vice_captain: None,
+            previous_captain_id: None,
+            gameweek_transfers: GameweekTransfers::default(),
+            bank_in_millions: None,
+            value_in_millions: None,
+            players: players.clone(),
+        };
+        let results = players
+            .iter()
+            .map(|player| ValidationResult {
+                rule_id: "price_limit".to_string(),
+                penalty_points: 4,
+                ..ValidationResult::invalid(&team, &format!("{} is too pricey", player.name))
+                    .with_offending_players(vec![player.clone()])
+            })
+            .collect();
+
+        TeamOutcome::Checked(Box::new(TeamReport {
+            team,
+            results,
+            health_check: Vec::new(),
+        }))
+    }
+
+    #[test]
+    fn should_not_double_count_violations_when_a_gameweek_is_rechecked() {
+        let (history, path) = history("dedup");
+        let outcome = report(1, &[10, 11]);
+
+        history
+            .record([(3, &outcome), (3, &outcome)])
+            .expect("Something went wrong recording violations");
+        let first_recorded_at = history
+            .violations(Some(1), None)
+            .expect("Something went wrong querying history")[0]
+            .recorded_at
+            .clone();
+        history
+            .record([(3, &outcome)])
+            .expect("Something went wrong re-recording violations");
+
+        let records = history
+            .violations(Some(1), None)
+            .expect("Something went wrong querying history");
+        assert_that!(records.len()).is_equal_to(2);
+        assert_that!(records[0].offending_players.clone())
+            .is_equal_to(vec!["Player 10".to_string()]);
+        assert_that!(records[0].penalty_points).is_equal_to(4);
+        assert_that!(records[0].recorded_at.clone()).is_equal_to(first_recorded_at);
+
+        drop(history);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn should_drop_violations_no_longer_found_when_a_gameweek_is_rechecked() {
+        let (history, path) = history("recheck");
+
+        history
+            .record([(3, &report(1, &[10, 11]))])
+            .expect("Something went wrong recording violations");
+        history
+            .record([(3, &report(1, &[11]))])
+            .expect("Something went wrong re-recording violations");
+
+        let records = history
+            .violations(Some(1), Some(3))
+            .expect("Something went wrong querying history");
+        assert_that!(records.len()).is_equal_to(1);
+        assert_that!(records[0].offending_players.clone())
+            .is_equal_to(vec!["Player 11".to_string()]);
+
+        drop(history);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn should_query_violations_by_manager_and_gameweek() {
+        let (history, path) = history("query");
+
+        history
+            .record([
+                (3, &report(2, &[20])),
+                (3, &report(1, &[10])),
+                (4, &report(1, &[10])),
+                (
+                    4,
+                    &TeamOutcome::Failed {
+                        team_id: 3,
+                        error: FplCheckerError::HttpStatus(404),
+                    },
+                ),
+            ])
+            .expect("Something went wrong recording violations");
+
+        let by_manager = history
+            .violations(Some(1), None)
+            .expect("Something went wrong querying history");
+        let by_gameweek = history
+            .violations(None, Some(3))
+            .expect("Something went wrong querying history");
+        assert_that!(
+            by_manager
+                .iter()
+                .map(|record| record.gameweek)
+                .collect::<Vec<i64>>()
+        )
+        .is_equal_to(vec![3, 4]);
+        assert_that!(
+            by_gameweek
+                .iter()
+                .map(|record| record.team_id)
+                .collect::<Vec<i64>>()
+        )
+        .is_equal_to(vec![1, 2]);
+        assert_that!(
+            process_violation_history(by_gameweek)
+                .contains("== Team 2 (Owner 2) ==\nGW3 price_limit: ")
+        )
+        .is_true();
+
+        drop(history);
+        let _ = std::fs::remove_file(&path);
+    }
+}
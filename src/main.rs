@@ -1,120 +1,666 @@
-use crate::api::{fetch_gameweek_data, fetch_picks};
-use crate::builders::build_team_from_data;
-use crate::models::TeamsRequest;
-use constants::BOOTSTRAP_DATA_URI;
-use models::{BootstrapData, ValidationResult};
+use crate::api::{FplClient, RetryPolicy};
+use crate::cache::ResponseCache;
+use crate::checker::{outcomes_by_team_id, run_validation_for_teams};
+use crate::errors::FplCheckerError;
+use crate::history::ViolationHistory;
+use crate::models::{
+    H2hCheck, LedgerRow, RuleDescription, SeasonAudit, StandingsRow, Team, TeamOutcome, TeamReport,
+    TeamsRequest, ViolationRecord,
+};
+use crate::recorder::SnapshotRecorder;
+use crate::rulebook::Rulebook;
+use crate::rules::RuleRegistry;
+use indexmap::IndexMap;
+use models::Player;
 use rocket::serde::json::Json;
-use rocket::{build, post, routes, Build, Rocket};
+use rocket::{Build, Rocket, State, build, get, post, routes};
+use std::path::Path;
 
 mod api;
 mod builders;
+mod cache;
+mod checker;
 mod constants;
+mod errors;
+mod h2h;
+mod history;
 mod models;
+mod recorder;
+mod rulebook;
+mod rules;
+mod scoring;
+mod season;
+#[cfg(test)]
+mod test_support;
 mod validators;
 
+struct CliOptions {
+    api: bool,
+    json: bool,
+    season: bool,
+    ledger: bool,
+    standings: bool,
+    violations: bool,
+    rulebook_path: Option<String>,
+    cache_dir: Option<String>,
+    offline_dir: Option<String>,
+    record_dir: Option<String>,
+    history_path: Option<String>,
+    parallelism: Option<usize>,
+    gameweek: Option<i64>,
+    league: Option<i64>,
+    h2h_league: Option<i64>,
+    team_ids: Vec<i64>,
+}
+
 #[tokio::main]
 async fn main() {
-    let arguments: Vec<String> = std::env::args().collect();
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
 
-    if arguments.len() < 2 {
+    if arguments.is_empty() {
         print_usage();
         return;
     }
 
-    if arguments[1] == "--api" {
-        let _ = build_rocket().launch().await;
+    let options = match parse_cli_options(&arguments) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            print_usage();
+            std::process::exit(2);
+        }
+    };
+    let rulebook = match Rulebook::load(options.rulebook_path.as_deref()) {
+        Ok(rulebook) => rulebook,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(error.exit_code());
+        }
+    };
+    let registry = RuleRegistry::from_rulebook(&rulebook);
+    let client = FplClient::new()
+        .with_cache(ResponseCache::from_env(options.cache_dir.as_deref()))
+        .with_offline_dir(options.offline_dir.as_deref().map(Path::new))
+        .with_recorder(
+            options
+                .record_dir
+                .as_deref()
+                .map(|record_dir| SnapshotRecorder::new(Path::new(record_dir))),
+        )
+        .with_limits_from_env(options.parallelism)
+        .with_retry_policy(RetryPolicy::from_env());
+    let history = match ViolationHistory::from_env(options.history_path.as_deref()) {
+        Ok(history) => history,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(error.exit_code());
+        }
+    };
+    let request = TeamsRequest {
+        teams: options.team_ids,
+        league: options.league,
+        h2h_league: options.h2h_league,
+        gameweek: options.gameweek,
+    };
+
+    if options.api {
+        let _ = build_rocket(registry, client, history).launch().await;
+    } else if options.violations {
+        let records = match query_history(history.as_ref(), &request.teams, request.gameweek) {
+            Ok(records) => records,
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(error.exit_code());
+            }
+        };
+
+        if options.json {
+            println!("{}", to_json(&history::records_by_team_id(records)));
+        } else {
+            println!("{}", history::process_violation_history(records));
+        }
+    } else if options.season || options.ledger || options.standings {
+        let audits =
+            match season::run_season_audit(request, &registry, &client, history.as_ref(), "CLI")
+                .await
+            {
+                Ok(audits) => audits,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(error.exit_code());
+                }
+            };
+
+        let output = if options.standings {
+            let standings = scoring::build_adjusted_standings(&audits);
+            if options.json {
+                to_json(&standings)
+            } else {
+                scoring::process_adjusted_standings(&standings)
+            }
+        } else if options.ledger {
+            let table = scoring::build_adjusted_table(&audits);
+            if options.json {
+                to_json(&table)
+            } else {
+                scoring::process_adjusted_table(&table)
+            }
+        } else if options.json {
+            to_json(&season::audits_by_team_id(audits))
+        } else {
+            season::process_season_audits(&audits)
+        };
+        println!("{}", output);
+    } else if let Some(h2h_league) = request.h2h_league {
+        let check = match h2h::run_h2h_check(
+            h2h_league,
+            request.gameweek,
+            &registry,
+            &client,
+            history.as_ref(),
+            "CLI",
+        )
+        .await
+        {
+            Ok(check) => check,
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(error.exit_code());
+            }
+        };
+
+        if options.json {
+            println!("{}", to_json(&check));
+        } else {
+            println!(
+                "{}",
+                process_team_outcomes(check.teams.values().cloned().collect())
+            );
+            println!("{}", h2h::process_h2h_check(&check));
+        }
     } else {
-        let team_ids: Vec<i64> = parse_team_ids_from_cli();
-        let violations = run_validation_for_teams(team_ids, "CLI");
+        let outcomes =
+            match run_validation_for_teams(request, &registry, &client, history.as_ref(), "CLI")
+                .await
+            {
+                Ok(outcomes) => outcomes,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(error.exit_code());
+                }
+            };
+        let exit_code = outcomes
+            .iter()
+            .find_map(|outcome| match outcome {
+                TeamOutcome::Failed { error, .. } => Some(error.exit_code()),
+                TeamOutcome::Checked(_) => None,
+            })
+            .unwrap_or(0);
+
+        if options.json {
+            println!("{}", to_json(&outcomes_by_team_id(outcomes)));
+        } else {
+            println!("{}", process_team_outcomes(outcomes));
+        }
 
-        println!("{}", process_validation_results(violations));
+        std::process::exit(exit_code);
     }
 }
 
 #[post("/api", data = "<input>")]
-fn handle_teams_request(input: Json<TeamsRequest>) -> Json<Vec<ValidationResult>> {
-    let violations = run_validation_for_teams(input.teams.clone(), "API");
-    Json(violations)
+async fn handle_teams_request(
+    input: Json<TeamsRequest>,
+    registry: &State<RuleRegistry>,
+    client: &State<FplClient>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<IndexMap<i64, TeamOutcome>>, FplCheckerError> {
+    validate_teams_request(&input)?;
+
+    let outcomes = run_validation_for_teams(
+        input.into_inner(),
+        registry,
+        client,
+        history.inner().as_ref(),
+        "API",
+    )
+    .await?;
+    Ok(Json(outcomes_by_team_id(outcomes)))
 }
 
-fn build_rocket() -> Rocket<Build> {
-    build().mount("/", routes![handle_teams_request])
+#[post("/api/season", data = "<input>")]
+async fn handle_season_request(
+    input: Json<TeamsRequest>,
+    registry: &State<RuleRegistry>,
+    client: &State<FplClient>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<IndexMap<i64, SeasonAudit>>, FplCheckerError> {
+    validate_teams_request(&input)?;
+
+    let audits = season::run_season_audit(
+        input.into_inner(),
+        registry,
+        client,
+        history.inner().as_ref(),
+        "API",
+    )
+    .await?;
+    Ok(Json(season::audits_by_team_id(audits)))
 }
 
-fn run_validation_for_teams(team_ids: Vec<i64>, source: &str) -> Vec<ValidationResult> {
-    println!("Checking teams {:?} from {}", team_ids, source);
-    let bootstrap_data: BootstrapData = api::fetch_data_as_json(BOOTSTRAP_DATA_URI)
-        .expect("Something went wrong fetching bootstrap data");
-    let clubs_by_club_id = builders::build_clubs_by_id(&bootstrap_data);
-    let players_by_id = builders::build_players_by_id(&clubs_by_club_id, &bootstrap_data);
-    let current_gameweek = builders::get_current_gameweek(&bootstrap_data);
+/// Rejects bad ids up front so a typo fails the whole request rather than each team.
+fn validate_teams_request(request: &TeamsRequest) -> Result<(), FplCheckerError> {
+    for team_id in &request.teams {
+        api::validate_team_id(team_id)?;
+    }
+    for league_id in request.league.iter().chain(&request.h2h_league) {
+        api::validate_league_id(league_id)?;
+    }
+    if let Some(gameweek) = request.gameweek {
+        api::validate_gameweek(gameweek)?;
+    }
 
-    println!("Checking gameweek {}...", current_gameweek);
+    Ok(())
+}
 
-    let mut validation_results: Vec<ValidationResult> = Vec::new();
-    let mut violations: Vec<ValidationResult> = Vec::new();
+#[post("/api/ledger", data = "<input>")]
+async fn handle_ledger_request(
+    input: Json<TeamsRequest>,
+    registry: &State<RuleRegistry>,
+    client: &State<FplClient>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<Vec<LedgerRow>>, FplCheckerError> {
+    validate_teams_request(&input)?;
 
-    for fpl_team_id in team_ids {
-        let gameweek_data = fetch_gameweek_data(&fpl_team_id);
-        let picks_data = fetch_picks(&fpl_team_id, &gameweek_data);
-        let team = build_team_from_data(fpl_team_id, &players_by_id, &gameweek_data, &picks_data);
+    let audits = season::run_season_audit(
+        input.into_inner(),
+        registry,
+        client,
+        history.inner().as_ref(),
+        "API",
+    )
+    .await?;
+    Ok(Json(scoring::build_adjusted_table(&audits)))
+}
 
-        violations = validators::run_validators_and_retain_violations(
-            &clubs_by_club_id,
-            &mut validation_results,
-            &team,
-        );
+#[get("/api/leagues/<league_id>/standings")]
+async fn handle_standings_request(
+    league_id: i64,
+    registry: &State<RuleRegistry>,
+    client: &State<FplClient>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<Vec<StandingsRow>>, FplCheckerError> {
+    api::validate_league_id(&league_id)?;
+    let request = TeamsRequest {
+        league: Some(league_id),
+        ..TeamsRequest::default()
+    };
+
+    let audits =
+        season::run_season_audit(request, registry, client, history.inner().as_ref(), "API")
+            .await?;
+    Ok(Json(scoring::build_adjusted_standings(&audits)))
+}
+
+#[get("/api/leagues-h2h/<league_id>?<gameweek>")]
+async fn handle_h2h_request(
+    league_id: i64,
+    gameweek: Option<i64>,
+    registry: &State<RuleRegistry>,
+    client: &State<FplClient>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<H2hCheck>, FplCheckerError> {
+    api::validate_league_id(&league_id)?;
+    if let Some(gameweek) = gameweek {
+        api::validate_gameweek(gameweek)?;
     }
 
-    violations
+    let check = h2h::run_h2h_check(
+        league_id,
+        gameweek,
+        registry,
+        client,
+        history.inner().as_ref(),
+        "API",
+    )
+    .await?;
+    Ok(Json(check))
 }
 
-fn process_validation_results(violations: Vec<ValidationResult>) -> String {
-    if violations.is_empty() {
+#[get("/api/rules")]
+fn handle_rules_request(registry: &State<RuleRegistry>) -> Json<Vec<RuleDescription>> {
+    Json(registry.describe())
+}
+
+#[get("/api/history?<gameweek>")]
+fn handle_history_request(
+    gameweek: Option<i64>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<IndexMap<i64, Vec<ViolationRecord>>>, FplCheckerError> {
+    let records = query_history(history.inner().as_ref(), &[], gameweek)?;
+    Ok(Json(history::records_by_team_id(records)))
+}
+
+#[get("/api/history/teams/<team_id>?<gameweek>")]
+fn handle_team_history_request(
+    team_id: i64,
+    gameweek: Option<i64>,
+    history: &State<Option<ViolationHistory>>,
+) -> Result<Json<Vec<ViolationRecord>>, FplCheckerError> {
+    api::validate_team_id(&team_id)?;
+
+    let records = query_history(history.inner().as_ref(), &[team_id], gameweek)?;
+    Ok(Json(records))
+}
+
+/// Looks up recorded violations for the given teams (every team if none are given), optionally
+/// narrowed to one gameweek.
+fn query_history(
+    history: Option<&ViolationHistory>,
+    team_ids: &[i64],
+    gameweek: Option<i64>,
+) -> Result<Vec<ViolationRecord>, FplCheckerError> {
+    let history = history.ok_or_else(|| {
+        FplCheckerError::Storage(format!(
+            "no history database configured, pass --history or set {}",
+            constants::HISTORY_DB_ENV
+        ))
+    })?;
+    if let Some(gameweek) = gameweek {
+        api::validate_gameweek(gameweek)?;
+    }
+    if team_ids.is_empty() {
+        return history.violations(None, gameweek);
+    }
+
+    let mut records = Vec::new();
+    for team_id in team_ids {
+        records.extend(history.violations(Some(*team_id), gameweek)?);
+    }
+
+    Ok(records)
+}
+
+fn build_rocket(
+    registry: RuleRegistry,
+    client: FplClient,
+    history: Option<ViolationHistory>,
+) -> Rocket<Build> {
+    build()
+        .manage(registry)
+        .manage(client)
+        .manage(history)
+        .mount(
+            "/",
+            routes![
+                handle_teams_request,
+                handle_season_request,
+                handle_ledger_request,
+                handle_standings_request,
+                handle_h2h_request,
+                handle_history_request,
+                handle_team_history_request,
+                handle_rules_request
+            ],
+        )
+}
+
+fn process_team_outcomes(outcomes: Vec<TeamOutcome>) -> String {
+    let reports: Vec<&TeamReport> = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            TeamOutcome::Checked(report) => Some(report.as_ref()),
+            TeamOutcome::Failed { .. } => None,
+        })
+        .collect();
+    if reports.len() == outcomes.len()
+        && reports
+            .iter()
+            .all(|report| report.violations().is_empty() && report.health_check.is_empty())
+    {
         return "No rules have been broken... boring!".to_string();
     }
 
     let mut output = String::new();
-    for validation in violations {
-        if !validation.is_valid {
-            output.push_str(&(validation.reason + "\n\n").to_string())
+    for outcome in &outcomes {
+        match outcome {
+            TeamOutcome::Checked(report) => {
+                output.push_str(&format!(
+                    "== {} ({}) ==\n",
+                    report.team.name, report.team.owner
+                ));
+
+                let violations = report.violations();
+                if violations.is_empty() {
+                    output.push_str("No rules broken\n\n");
+                }
+                for validation in violations {
+                    output.push_str(&format!("{}\n\n", validation.message));
+                }
+                if let Some(funds) = describe_funds(&report.team) {
+                    output.push_str(&format!("{}\n\n", funds));
+                }
+                if !report.health_check.is_empty() {
+                    output.push_str("Health check:\n");
+                    for player in &report.health_check {
+                        output.push_str(&format!("- {}\n", describe_availability(player)));
+                    }
+                    output.push('\n');
+                }
+            }
+            TeamOutcome::Failed { team_id, error } => {
+                output.push_str(&format!(
+                    "== Team {} ==\nCould not check: {}\n\n",
+                    team_id, error
+                ));
+            }
         }
     }
 
     output
 }
 
-fn parse_team_ids_from_cli() -> Vec<i64> {
-    std::env::args()
-        .skip(1)
-        .map(|arg| {
-            arg.parse::<i64>()
-                .unwrap_or_else(|_| panic!("Invalid team ID: {}", arg))
-        })
-        .collect()
+fn to_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string_pretty(value).expect("Something went wrong serialising output")
+}
+
+fn describe_funds(team: &Team) -> Option<String> {
+    Some(format!(
+        "Team value {:.1}m ({:.1}m in the bank)",
+        team.value_in_millions?, team.bank_in_millions?
+    ))
+}
+
+fn describe_availability(player: &Player) -> String {
+    let Some(availability) = &player.availability else {
+        return format!("{} is available", player.name);
+    };
+
+    let mut description = format!("{} is {}", player.name, availability.status.description());
+    if !availability.news.is_empty() {
+        description.push_str(&format!(": {}", availability.news));
+    } else if let Some(chance) = availability.chance_of_playing_next_round {
+        description.push_str(&format!(" ({}% chance of playing)", chance));
+    }
+
+    description
+}
+
+fn parse_cli_options(arguments: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions {
+        api: false,
+        json: false,
+        season: false,
+        ledger: false,
+        standings: false,
+        violations: false,
+        rulebook_path: None,
+        cache_dir: None,
+        offline_dir: None,
+        record_dir: None,
+        history_path: None,
+        parallelism: None,
+        gameweek: None,
+        league: None,
+        h2h_league: None,
+        team_ids: Vec::new(),
+    };
+    let mut arguments = arguments.iter();
+
+    while let Some(arg) = arguments.next() {
+        match arg.as_str() {
+            "--api" => options.api = true,
+            "--json" => options.json = true,
+            "--season" => options.season = true,
+            "--ledger" => options.ledger = true,
+            "--standings" => options.standings = true,
+            "--violations" => options.violations = true,
+            "--rulebook" => {
+                options.rulebook_path = Some(
+                    arguments
+                        .next()
+                        .ok_or("--rulebook requires a path")?
+                        .clone(),
+                )
+            }
+            "--cache-dir" => {
+                options.cache_dir = Some(
+                    arguments
+                        .next()
+                        .ok_or("--cache-dir requires a directory")?
+                        .clone(),
+                )
+            }
+            "--offline" => {
+                options.offline_dir = Some(
+                    arguments
+                        .next()
+                        .ok_or("--offline requires a directory")?
+                        .clone(),
+                )
+            }
+            "--record" => {
+                options.record_dir = Some(
+                    arguments
+                        .next()
+                        .ok_or("--record requires a directory")?
+                        .clone(),
+                )
+            }
+            "--history" => {
+                options.history_path =
+                    Some(arguments.next().ok_or("--history requires a path")?.clone())
+            }
+            "--parallelism" => {
+                let parallelism = arguments.next().ok_or("--parallelism requires a number")?;
+                options.parallelism = Some(
+                    parallelism
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid parallelism: {}", parallelism))?,
+                )
+            }
+            "--gameweek" => {
+                let gameweek = arguments.next().ok_or("--gameweek requires a number")?;
+                options.gameweek = Some(
+                    gameweek
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid gameweek: {}", gameweek))?,
+                )
+            }
+            "--league" => {
+                let league = arguments.next().ok_or("--league requires a league ID")?;
+                options.league = Some(
+                    league
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid league ID: {}", league))?,
+                )
+            }
+            "--h2h-league" => {
+                let league = arguments
+                    .next()
+                    .ok_or("--h2h-league requires a league ID")?;
+                options.h2h_league = Some(
+                    league
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid league ID: {}", league))?,
+                )
+            }
+            _ => options.team_ids.push(
+                arg.parse::<i64>()
+                    .map_err(|_| format!("Invalid team ID: {}", arg))?,
+            ),
+        }
+    }
+
+    if (options.season || options.ledger || options.standings) && options.gameweek.is_some() {
+        return Err(
+            "--season, --ledger and --standings cover every finished gameweek and cannot be combined with --gameweek"
+                .to_string(),
+        );
+    }
+
+    if options.h2h_league.is_some()
+        && !(options.season || options.ledger || options.standings)
+        && (options.league.is_some() || !options.team_ids.is_empty())
+    {
+        return Err(
+            "--h2h-league checks the league's own members and cannot be combined with --league or team IDs"
+                .to_string(),
+        );
+    }
+
+    if options.violations && (options.league.is_some() || options.h2h_league.is_some()) {
+        return Err(
+            "--violations reads from the violation history and takes team IDs rather than leagues"
+                .to_string(),
+        );
+    }
+
+    Ok(options)
 }
 
 fn print_usage() {
-    println!("Usage: fpl-checker <team_id> [<team_id> ...]");
-    println!("       fpl-checker --api");
+    println!(
+        "Usage: fpl-checker [--rulebook <path>] [--cache-dir <dir> | --offline <dir>] [--record <dir>] [--history <path>] [--parallelism <n>] [--gameweek <n> | --season | --ledger | --standings] [--json] [--league <id>] [--h2h-league <id>] [<team_id> ...]"
+    );
+    println!(
+        "       fpl-checker --history <path> --violations [--gameweek <n>] [--json] [<team_id> ...]"
+    );
+    println!(
+        "       fpl-checker [--rulebook <path>] [--cache-dir <dir> | --offline <dir>] [--record <dir>] [--history <path>] [--parallelism <n>] --api"
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::fetch_data_as_json;
-    use crate::builders::{build_clubs_by_id, build_players_by_id, build_team_from_data};
-    use crate::models::{Club, GameweekData, PicksData, Player, Team};
+    use crate::builders::{
+        build_clubs_by_id, build_players_by_id, build_team_from_data, get_current_gameweek,
+    };
+    use crate::constants::NEWLY_PROMOTED_CLUBS;
+    use crate::models::{
+        Availability, BootstrapData, Chip, Club, ElementHistory, ElementSummary, GameweekData,
+        GameweekTransfers, PicksData, PlayLimits, Player, PlayerStatus, Position, PriceBasis,
+        RuleScope, Severity, Team, Transfer, TransferData, ValidationResult,
+    };
+    use crate::rulebook::{Penalty, PenaltyKind};
+    use crate::rules::{Rule, RuleContext};
+    use crate::test_support::write_snapshots;
     use crate::validators::{
-        team_contains_at_most_one_player_per_club, team_contains_players_from_newly_promoted_clubs,
-        team_contains_players_under_10_m,
+        team_captains_from_clubs, team_captains_under_price_limit,
+        team_contains_at_most_n_players_per_club, team_contains_players_from_newly_promoted_clubs,
+        team_contains_players_under_price_limit, team_contains_players_within_position_limit,
+        team_keeps_minimum_bank, team_replaces_promoted_club_players,
+        team_squad_value_within_limit, team_takes_hits_within_limit,
     };
     use assertor::*;
+    use rocket::http::Status;
     use serde_json::from_str;
+    use std::collections::HashMap;
 
     const BOOTSTRAP_JSON: &str = include_str!("../tests/samples/bootstrap.json");
     const GAMEWEEK_JSON: &str = include_str!("../tests/samples/gameweek.json");
     const PICKS_JSON: &str = include_str!("../tests/samples/picks.json");
+    const TRANSFERS_JSON: &str = include_str!("../tests/samples/transfers.json");
     const VALID_TEAM_JSON: &str = include_str!("../tests/samples/valid_team.json");
     const INVALID_TEAM_JSON: &str = include_str!("../tests/samples/invalid_team.json");
     const INVALID_TEAM_DUPLICATE_ARSENAL_JSON: &str =
@@ -129,7 +675,7 @@ mod tests {
     #[test]
     fn should_build_clubs_by_club_id_from_bootstrap_data() {
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let actual = build_clubs_by_id(&bootstrap_data);
 
         assert_that!(&"Arsenal".to_string())
@@ -152,12 +698,15 @@ mod tests {
                 id: 7,
                 name: "Chelsea".to_string(),
             },
+            position: Some(Position::Forward),
+            ..Player::default()
         };
 
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let actual = build_players_by_id(&clubs_by_club_id, &bootstrap_data);
+        let actual = build_players_by_id(&clubs_by_club_id, &bootstrap_data)
+            .expect("Something went wrong building players");
 
         assert_that!(actual.get(&partial_expected.id)).is_equal_to(Some(&partial_expected));
     }
@@ -165,103 +714,296 @@ mod tests {
     #[test]
     fn should_build_team_from_data() {
         let expected: Team =
-            from_str(&VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
 
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let gameweek_data: GameweekData =
-            from_str(&GAMEWEEK_JSON).expect("Something went wrong parsing gameweek data");
+            from_str(GAMEWEEK_JSON).expect("Something went wrong parsing gameweek data");
         let picks_data: PicksData =
-            from_str(&PICKS_JSON).expect("Something went wrong parsing picks data");
+            from_str(PICKS_JSON).expect("Something went wrong parsing picks data");
 
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let players_by_player_id = build_players_by_id(&clubs_by_club_id.clone(), &bootstrap_data);
+        let players_by_player_id = build_players_by_id(&clubs_by_club_id.clone(), &bootstrap_data)
+            .expect("Something went wrong building players");
         let actual =
-            build_team_from_data(2239760, &players_by_player_id, &gameweek_data, &picks_data);
+            build_team_from_data(2239760, &players_by_player_id, &gameweek_data, &picks_data)
+                .expect("Something went wrong building team");
 
         assert_that!(actual).is_equal_to(expected);
     }
 
     #[test]
     fn should_fail_if_team_has_more_than_one_player_from_a_club() {
-        let team = from_str(&INVALID_TEAM_DUPLICATE_ARSENAL_JSON)
+        let team = from_str(INVALID_TEAM_DUPLICATE_ARSENAL_JSON)
             .expect("Something went wrong parsing invalid team");
-        let actual = team_contains_at_most_one_player_per_club(&team);
+        let actual = team_contains_at_most_n_players_per_club(&team, 1);
 
-        assert_that!(actual.reason)
+        assert_that!(actual.message)
             .contains("has more than 1 player from Arsenal (Gabriel and Gyökeres)");
     }
 
+    #[test]
+    fn should_report_offending_players_and_clubs_for_club_limit_violation() {
+        let team: Team = from_str(INVALID_TEAM_DUPLICATE_ARSENAL_JSON)
+            .expect("Something went wrong parsing invalid team");
+        let actual = team_contains_at_most_n_players_per_club(&team, 1);
+
+        let offending_player_names: Vec<String> = actual
+            .offending_players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect();
+        let offending_club_names: Vec<String> = actual
+            .offending_clubs
+            .iter()
+            .map(|club| club.name.clone())
+            .collect();
+
+        assert_that!(actual.team_id).is_equal_to(team.id);
+        assert_that!(actual.owner).is_equal_to(team.owner);
+        assert_that!(actual.is_valid).is_false();
+        assert_that!(offending_player_names)
+            .is_equal_to(vec!["Gabriel".to_string(), "Gyökeres".to_string()]);
+        assert_that!(offending_club_names).is_equal_to(vec!["Arsenal".to_string()]);
+    }
+
+    #[test]
+    fn should_stamp_rule_id_and_severity_on_results_from_registry() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let team: Team =
+            from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+
+        let actual = RuleRegistry::from_rulebook(&Rulebook::default()).evaluate(
+            &team,
+            &RuleContext {
+                clubs_by_club_id: &clubs_by_club_id,
+                play_limits_by_position: &IndexMap::new(),
+            },
+        );
+
+        assert_that!(actual[0].rule_id).is_equal_to("price_limit".to_string());
+        assert_that!(actual[0].severity).is_equal_to(Severity::Violation);
+        assert_that!(actual[0].offending_players.len()).is_equal_to(2);
+    }
+
     #[test]
     fn should_fail_if_team_has_more_than_a_few_players_from_multiple_clubs() {
-        let team = from_str(&INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+        let team = from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
             .expect("Something went wrong parsing invalid team");
-        let actual = team_contains_at_most_one_player_per_club(&team);
+        let actual = team_contains_at_most_n_players_per_club(&team, 1);
 
-        assert_that!(actual.reason)
+        assert_that!(actual.message)
             .contains("has more than 1 player from Chelsea (Sánchez and João Pedro) more than 1 player from Arsenal (Gabriel, Saliba and Gyökeres) more than 1 player from Man Utd (Yoro and Mbeumo)");
     }
 
     #[test]
     fn should_pass_if_team_does_not_have_more_than_one_player_from_a_club() {
         let team: Team =
-            from_str(&VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
-        let actual = team_contains_at_most_one_player_per_club(&team);
-        let expected = ValidationResult::valid();
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        let actual = team_contains_at_most_n_players_per_club(&team, 1);
+        let expected = ValidationResult::valid(&team);
 
         assert_that!(actual).is_equal_to(expected);
     }
 
     #[test]
     fn should_fail_if_team_has_player_above_price_limit() {
-        let team = from_str(&INVALID_TEAM_MISSING_PLAYER_OVER_10M)
+        let team = from_str(INVALID_TEAM_MISSING_PLAYER_OVER_10M)
             .expect("Something went wrong parsing invalid team");
-        let actual = team_contains_players_under_10_m(&team);
+        let actual = team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current);
 
-        assert_that!(actual.reason).contains("has gone overbudget with Haaland (14m)");
+        assert_that!(actual.message).contains("has gone overbudget with Haaland (14m)");
     }
 
     #[test]
     fn should_produce_multiple_failures_if_team_has_more_than_1_player_above_price_limit() {
-        let team = from_str(&INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
-        let actual = team_contains_players_under_10_m(&team);
+        let team = from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+        let actual = team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current);
 
-        assert_that!(actual.reason)
+        assert_that!(actual.message)
             .contains("has gone overbudget with Palmer (10.5m) and Haaland (14m)");
     }
 
     #[test]
     fn should_pass_if_team_has_players_under_price_limit() {
         let team: Team =
-            from_str(&VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
-        let actual = team_contains_players_under_10_m(&team);
-        let expected = ValidationResult::valid();
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        let actual = team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current);
+        let expected = ValidationResult::valid(&team);
 
         assert_that!(actual).is_equal_to(expected);
     }
 
+    #[test]
+    fn should_enforce_price_limit_on_chosen_price_basis() {
+        let bought_before_rise = Player {
+            id: 1,
+            name: "Riser".to_string(),
+            price_in_millions: 10.0,
+            ..Player::default()
+        };
+        let team = Team {
+            id: 1,
+            name: "Risers".to_string(),
+            owner: "Sam".to_string(),
+            points: 0,
+            active_chip: None,
+            captain: bought_before_rise.clone(),
+            vice_captain: None,
+            previous_captain_id: None,
+            gameweek_transfers: GameweekTransfers::default(),
+            bank_in_millions: None,
+            value_in_millions: None,
+            players: vec![bought_before_rise],
+        };
+        let start_prices = HashMap::from([(1, 9.5)]);
+
+        let team = builders::apply_transfer_prices(team, &[], &start_prices, 1);
+
+        assert_that!(team.players[0].purchase_price_in_millions).is_equal_to(Some(9.5));
+        assert_that!(team.players[0].selling_price_in_millions).is_equal_to(Some(9.7));
+        assert_that!(
+            team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current).is_valid
+        )
+        .is_false();
+        assert_that!(
+            team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Purchase).is_valid
+        )
+        .is_true();
+        assert_that!(
+            team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Selling).is_valid
+        )
+        .is_true();
+    }
+
+    #[test]
+    fn should_take_purchase_price_from_latest_transfer_up_to_gameweek() {
+        let transfers: Vec<TransferData> =
+            from_str(TRANSFERS_JSON).expect("Something went wrong parsing transfers");
+        let bowen = Player {
+            id: 624,
+            name: "Bowen".to_string(),
+            price_in_millions: 8.0,
+            ..Player::default()
+        };
+        let team = Team {
+            id: 2239760,
+            name: "Team".to_string(),
+            owner: "Sam".to_string(),
+            points: 0,
+            active_chip: None,
+            captain: bowen.clone(),
+            vice_captain: None,
+            previous_captain_id: None,
+            gameweek_transfers: GameweekTransfers::default(),
+            bank_in_millions: None,
+            value_in_millions: None,
+            players: vec![bowen],
+        };
+        let start_prices = HashMap::from([(624, 8.0)]);
+
+        let before_transfer =
+            builders::apply_transfer_prices(team.clone(), &transfers, &start_prices, 1);
+        let after_transfer = builders::apply_transfer_prices(team, &transfers, &start_prices, 2);
+
+        assert_that!(before_transfer.players[0].purchase_price_in_millions).is_equal_to(Some(8.0));
+        assert_that!(after_transfer.players[0].purchase_price_in_millions).is_equal_to(Some(7.8));
+        assert_that!(after_transfer.captain.selling_price_in_millions).is_equal_to(Some(7.9));
+    }
+
+    #[test]
+    fn should_price_a_late_joiners_first_squad_at_the_gameweek_they_joined() {
+        let bowen = Player {
+            id: 624,
+            name: "Bowen".to_string(),
+            price_in_millions: 8.0,
+            ..Player::default()
+        };
+        let no_history = Player {
+            id: 1,
+            name: "Raya".to_string(),
+            price_in_millions: 5.5,
+            ..Player::default()
+        };
+        let team = Team {
+            id: 2239760,
+            name: "Team".to_string(),
+            owner: "Sam".to_string(),
+            points: 0,
+            active_chip: None,
+            captain: bowen.clone(),
+            vice_captain: None,
+            previous_captain_id: None,
+            gameweek_transfers: GameweekTransfers::default(),
+            bank_in_millions: None,
+            value_in_millions: None,
+            players: vec![bowen, no_history],
+        };
+        let start_prices = HashMap::from([(624, 7.5), (1, 5.5)]);
+        let summaries_by_id = HashMap::from([(
+            624,
+            ElementSummary {
+                history: vec![
+                    ElementHistory {
+                        round: 1,
+                        value: 75.0,
+                    },
+                    ElementHistory {
+                        round: 4,
+                        value: 77.0,
+                    },
+                    ElementHistory {
+                        round: 6,
+                        value: 80.0,
+                    },
+                ],
+            },
+        )]);
+
+        let from_the_start = builders::build_first_squad_prices(&start_prices, &summaries_by_id, 1);
+        let late_joiner = builders::build_first_squad_prices(&start_prices, &summaries_by_id, 5);
+        let team = builders::apply_transfer_prices(team, &[], &late_joiner, 6);
+
+        assert_that!(from_the_start).is_equal_to(start_prices);
+        assert_that!(team.players[0].purchase_price_in_millions).is_equal_to(Some(7.7));
+        assert_that!(team.players[0].selling_price_in_millions).is_equal_to(Some(7.8));
+        assert_that!(team.players[1].purchase_price_in_millions).is_none();
+        assert_that!(team.players[1].selling_price_in_millions).is_none();
+    }
+
     #[test]
     fn should_fail_if_team_does_not_have_players_from_newly_promoted_clubs() {
         let team = from_str(INVALID_TEAM_MISSING_BURNLEY)
             .expect("Something went wrong parsing invalid team");
 
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let actual = team_contains_players_from_newly_promoted_clubs(&clubs_by_club_id, &team);
+        let actual = team_contains_players_from_newly_promoted_clubs(
+            &clubs_by_club_id,
+            &NEWLY_PROMOTED_CLUBS,
+            &team,
+        );
 
-        assert_that!(actual.reason).contains("has not included players from Burnley")
+        assert_that!(actual.message).contains("has not included players from Burnley")
     }
 
     #[test]
     fn should_pass_if_team_has_players_from_newly_promoted_clubs() {
         let team: Team =
-            from_str(&VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let actual = team_contains_players_from_newly_promoted_clubs(&clubs_by_club_id, &team);
-        let expected = ValidationResult::valid();
+        let actual = team_contains_players_from_newly_promoted_clubs(
+            &clubs_by_club_id,
+            &NEWLY_PROMOTED_CLUBS,
+            &team,
+        );
+        let expected = ValidationResult::valid(&team);
 
         assert_that!(actual).is_equal_to(expected);
     }
@@ -269,39 +1011,1355 @@ mod tests {
     #[test]
     fn should_pass_all_validation_rules_if_valid() {
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let team = from_str(&VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
-        let mut validation_results: Vec<ValidationResult> = Vec::new();
+        let team = from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        let report = validators::run_validators(
+            &RuleRegistry::from_rulebook(&Rulebook::default()),
+            &RuleContext {
+                clubs_by_club_id: &clubs_by_club_id,
+                play_limits_by_position: &IndexMap::new(),
+            },
+            &team,
+        );
+        let violations = report.violations();
 
-        let violations = validators::run_validators_and_retain_violations(
-            &clubs_by_club_id,
-            &mut validation_results,
+        assert_that!(violations.is_empty()).is_true()
+    }
+
+    #[test]
+    fn should_exempt_free_hit_squads_from_promoted_clubs_rule() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let mut team: Team = from_str(INVALID_TEAM_MISSING_BURNLEY)
+            .expect("Something went wrong parsing invalid team");
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let promoted_clubs_is_valid = |team: &Team| {
+            registry
+                .evaluate(team, &context)
+                .into_iter()
+                .find(|result| result.rule_id == "promoted_clubs")
+                .map(|result| result.is_valid)
+        };
+
+        let without_chip = promoted_clubs_is_valid(&team);
+        team.active_chip = Some(Chip::FreeHit);
+        let on_free_hit = promoted_clubs_is_valid(&team);
+        team.active_chip = Some(Chip::Wildcard);
+        let on_wildcard = promoted_clubs_is_valid(&team);
+
+        assert_that!(without_chip).is_equal_to(Some(false));
+        assert_that!(on_free_hit).is_equal_to(Some(true));
+        assert_that!(on_wildcard).is_equal_to(Some(false));
+    }
+
+    #[test]
+    fn should_doubly_penalise_triple_captained_player_above_price_limit() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let mut team: Team =
+            from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+        let mut rulebook = Rulebook::default();
+        rulebook.penalties.insert(
+            "price_limit".to_string(),
+            Penalty {
+                kind: PenaltyKind::PerPlayer,
+                points: 4,
+            },
+        );
+        let registry = RuleRegistry::from_rulebook(&rulebook);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        team.captain = team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current)
+            .offending_players[0]
+            .clone();
+
+        let without_chip = registry.evaluate(&team, &context)[0].penalty_points;
+        team.active_chip = Some(Chip::TripleCaptain);
+        let triple_captained = registry.evaluate(&team, &context).remove(0);
+
+        assert_that!(without_chip).is_equal_to(8);
+        assert_that!(triple_captained.penalty_points).is_equal_to(12);
+        assert_that!(triple_captained.chip_penalised_players).is_equal_to(1);
+        assert_that!(
+            triple_captained
+                .message
+                .contains(&format!("triple captained {}", team.captain.name))
+        )
+        .is_true();
+    }
+
+    #[test]
+    fn should_only_check_starting_xi_for_rules_scoped_to_it() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let mut team: Team =
+            from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+        let benched_ids: Vec<i64> =
+            team_contains_players_under_price_limit(&team, 10.0, PriceBasis::Current)
+                .offending_players
+                .iter()
+                .map(|player| player.id)
+                .collect();
+        for (index, player) in team.players.iter_mut().enumerate() {
+            let is_benched = benched_ids.contains(&player.id);
+            player.lineup_slot = Some(if is_benched { 12 } else { index as i64 + 1 });
+            player.multiplier = Some(if is_benched { 0 } else { 1 });
+        }
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let price_limit_is_valid = |scope: RuleScope| {
+            let mut rulebook = Rulebook::default();
+            rulebook.price_limit.scope = scope;
+            RuleRegistry::from_rulebook(&rulebook).evaluate(&team, &context)[0].is_valid
+        };
+
+        assert_that!(price_limit_is_valid(RuleScope::Squad)).is_false();
+        assert_that!(price_limit_is_valid(RuleScope::StartingXi)).is_true();
+    }
+
+    #[test]
+    fn should_not_count_bench_boosted_bench_as_starting() {
+        let bench_boosted = Player {
+            lineup_slot: Some(13),
+            multiplier: Some(1),
+            ..Player::default()
+        };
+        let subbed_off = Player {
+            lineup_slot: Some(4),
+            multiplier: Some(0),
+            ..Player::default()
+        };
+
+        assert_that!(bench_boosted.is_starting()).is_false();
+        assert_that!(subbed_off.is_starting()).is_false();
+        assert_that!(Player::default().is_starting()).is_true();
+    }
+
+    #[test]
+    fn should_limit_players_of_a_position_at_or_above_a_price() {
+        let team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let premium_forwards = team_contains_players_within_position_limit(
+            &team,
+            Position::Forward,
+            Some(7.0),
+            0,
+            Some(1),
+        );
+        let back_five = team_contains_players_within_position_limit(
+            &team.in_scope(RuleScope::StartingXi),
+            Position::Defender,
+            None,
+            5,
+            None,
+        );
+        let no_premium_goalkeeper = team_contains_players_within_position_limit(
+            &team,
+            Position::Goalkeeper,
+            Some(6.0),
+            0,
+            Some(0),
+        );
+
+        assert_that!(premium_forwards.is_valid).is_false();
+        assert_that!(premium_forwards.extra_players)
+            .is_equal_to(premium_forwards.offending_players.len() - 1);
+        assert_that!(
+            premium_forwards
+                .offending_players
+                .iter()
+                .all(|player| player.position == Some(Position::Forward))
+        )
+        .is_true();
+        assert_that!(back_five.is_valid).is_false();
+        assert_that!(
+            back_five
+                .message
+                .contains("fewer than 5 defenders (only 3)")
+        )
+        .is_true();
+        assert_that!(
+            premium_forwards
+                .message
+                .contains("more than 1 forward costing 7m or more:")
+        )
+        .is_true();
+        assert_that!(no_premium_goalkeeper.is_valid).is_true();
+    }
+
+    #[test]
+    fn should_check_starting_xi_fields_a_legal_formation() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let play_limits_by_position = builders::build_play_limits(&bootstrap_data);
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        let mut rulebook = Rulebook::default();
+        rulebook.formation.enabled = true;
+        let registry = RuleRegistry::from_rulebook(&rulebook);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &play_limits_by_position,
+        };
+        let formation = |team: &Team| {
+            registry
+                .evaluate(team, &context)
+                .into_iter()
+                .find(|result| result.rule_id == "formation")
+                .expect("Formation rule not registered")
+        };
+
+        let legal = formation(&team);
+        for player in team.players.iter_mut() {
+            if player.position == Some(Position::Goalkeeper) {
+                player.lineup_slot = Some(1);
+                player.multiplier = Some(1);
+            }
+        }
+        let two_goalkeepers = formation(&team);
+
+        assert_that!(play_limits_by_position[&Position::Defender])
+            .is_equal_to(PlayLimits { min: 3, max: 5 });
+        assert_that!(legal.is_valid).is_true();
+        assert_that!(two_goalkeepers.is_valid).is_false();
+        assert_that!(
+            two_goalkeepers
+                .message
+                .contains("2 goalkeepers (at most 1)")
+        )
+        .is_true();
+        assert_that!(two_goalkeepers.offending_players.len()).is_equal_to(2);
+    }
+
+    #[test]
+    fn should_cap_captain_and_optionally_vice_captain_price() {
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let captain_only = team_captains_under_price_limit(&team, 8.0, false);
+        let with_vice_captain = team_captains_under_price_limit(&team, 8.0, true);
+        team.active_chip = Some(Chip::TripleCaptain);
+        let triple_captained = team_captains_under_price_limit(&team, 7.0, false);
+
+        assert_that!(captain_only.is_valid).is_true();
+        assert_that!(with_vice_captain.is_valid).is_false();
+        assert_that!(
+            with_vice_captain
+                .message
+                .contains("vice-captained Bowen (8m)")
+        )
+        .is_true();
+        assert_that!(triple_captained.is_valid).is_false();
+        assert_that!(triple_captained.chip_penalised_players).is_equal_to(1);
+    }
+
+    #[test]
+    fn should_restrict_captain_to_listed_clubs() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let promoted_only =
+            team_captains_from_clubs(&clubs_by_club_id, &NEWLY_PROMOTED_CLUBS, &team, false);
+        let chelsea_or_west_ham =
+            team_captains_from_clubs(&clubs_by_club_id, &[7, 19], &team, true);
+
+        assert_that!(promoted_only.is_valid).is_false();
+        assert_that!(promoted_only.offending_players[0].name.clone())
+            .is_equal_to("João Pedro".to_string());
+        assert_that!(
+            promoted_only
+                .message
+                .contains("captains must play for Burnley")
+        )
+        .is_true();
+        assert_that!(chelsea_or_west_ham.is_valid).is_true();
+    }
+
+    #[test]
+    fn should_apply_gameweek_transfers_from_transfer_history() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let transfers: Vec<TransferData> =
+            from_str(TRANSFERS_JSON).expect("Something went wrong parsing transfers");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let players_by_player_id = build_players_by_id(&clubs_by_club_id, &bootstrap_data)
+            .expect("Something went wrong building players");
+        let team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let first_gameweek =
+            builders::apply_gameweek_transfers(team.clone(), &transfers, &players_by_player_id, 1)
+                .expect("Something went wrong applying transfers");
+        let second_gameweek =
+            builders::apply_gameweek_transfers(team, &transfers, &players_by_player_id, 2)
+                .expect("Something went wrong applying transfers");
+
+        assert_that!(first_gameweek.gameweek_transfers.transfers).is_empty();
+        assert_that!(second_gameweek.gameweek_transfers.transfers).has_length(1);
+        let transfer = &second_gameweek.gameweek_transfers.transfers[0];
+        assert_that!(transfer.player_in.name.clone()).is_equal_to("Bowen".to_string());
+        assert_that!(transfer.player_in.purchase_price_in_millions).is_equal_to(Some(7.8));
+        assert_that!(transfer.player_out.name.clone()).is_equal_to("M.Salah".to_string());
+        assert_that!(transfer.player_out.selling_price_in_millions).is_equal_to(Some(10.0));
+    }
+
+    #[test]
+    fn should_limit_hits_per_gameweek() {
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        team.gameweek_transfers = GameweekTransfers {
+            count: 3,
+            points_cost: 8,
+            transfers: Vec::new(),
+        };
+
+        let one_hit_allowed = team_takes_hits_within_limit(&team, 1);
+        let two_hits_allowed = team_takes_hits_within_limit(&team, 2);
+
+        assert_that!(one_hit_allowed.is_valid).is_false();
+        assert_that!(one_hit_allowed.extra_players).is_equal_to(1);
+        assert_that!(one_hit_allowed.message.contains("has taken 2 hits (-8)")).is_true();
+        assert_that!(two_hits_allowed.is_valid).is_true();
+    }
+
+    #[test]
+    fn should_require_promoted_club_players_to_be_replaced_from_promoted_clubs() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let player_at = |id: i64, name: &str, club_id: i64| Player {
+            id,
+            name: name.to_string(),
+            club: Club {
+                id: club_id,
+                name: clubs_by_club_id[&club_id].name.clone(),
+            },
+            ..Player::default()
+        };
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        team.gameweek_transfers.transfers = vec![Transfer {
+            player_in: player_at(624, "Bowen", 19),
+            player_out: player_at(1, "Burnley Player", 3),
+        }];
+
+        let sold_to_west_ham =
+            team_replaces_promoted_club_players(&clubs_by_club_id, &NEWLY_PROMOTED_CLUBS, &team);
+        team.gameweek_transfers.transfers[0].player_in = player_at(2, "Leeds Player", 11);
+        let sold_to_leeds =
+            team_replaces_promoted_club_players(&clubs_by_club_id, &NEWLY_PROMOTED_CLUBS, &team);
+
+        assert_that!(sold_to_west_ham.is_valid).is_false();
+        assert_that!(sold_to_west_ham.offending_players[0].name.clone())
+            .is_equal_to("Burnley Player".to_string());
+        assert_that!(
+            sold_to_west_ham
+                .message
+                .contains("has sold Burnley Player (Burnley) for Bowen (West Ham)")
+        )
+        .is_true();
+        assert_that!(sold_to_leeds.is_valid).is_true();
+    }
+
+    #[test]
+    fn should_limit_squad_value_excluding_the_bank() {
+        let team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let under_limit = team_squad_value_within_limit(&team, 96.0);
+        let over_limit = team_squad_value_within_limit(&team, 95.0);
+
+        assert_that!(team.squad_value_in_millions()).is_equal_to(Some(96.0));
+        assert_that!(under_limit.is_valid).is_true();
+        assert_that!(over_limit.is_valid).is_false();
+        assert_that!(
+            over_limit
+                .message
+                .contains("has a squad worth 96.0m, but squads may be worth at most 95.0m")
+        )
+        .is_true();
+    }
+
+    #[test]
+    fn should_require_a_minimum_bank() {
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let enough_in_the_bank = team_keeps_minimum_bank(&team, 1.0);
+        team.bank_in_millions = Some(0.5);
+        let too_little_in_the_bank = team_keeps_minimum_bank(&team, 1.0);
+        team.bank_in_millions = None;
+        let bank_unknown = team_keeps_minimum_bank(&team, 1.0);
+
+        assert_that!(enough_in_the_bank.is_valid).is_true();
+        assert_that!(too_little_in_the_bank.is_valid).is_false();
+        assert_that!(
+            too_little_in_the_bank
+                .message
+                .contains("has only 0.5m in the bank, but must keep at least 1.0m")
+        )
+        .is_true();
+        assert_that!(bank_unknown.is_valid).is_true();
+    }
+
+    #[test]
+    fn should_carry_availability_of_players_flagged_in_bootstrap() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let players_by_player_id = build_players_by_id(&clubs_by_club_id, &bootstrap_data)
+            .expect("Something went wrong building players");
+
+        assert_that!(players_by_player_id[&24].availability.clone()).is_equal_to(Some(
+            Availability {
+                status: PlayerStatus::Doubtful,
+                news: "Knock - 75% chance of playing".to_string(),
+                chance_of_playing_next_round: Some(75),
+            },
+        ));
+        assert_that!(players_by_player_id[&624].availability.clone()).is_none();
+    }
+
+    #[test]
+    fn should_health_check_starting_xi_and_captain() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let suspended = Availability {
+            status: PlayerStatus::Suspended,
+            news: "Suspended until 31 Aug".to_string(),
+            chance_of_playing_next_round: Some(0),
+        };
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        for player in team.players.iter_mut() {
+            player.availability = Some(suspended.clone());
+        }
+        team.captain.availability = Some(suspended);
+
+        let mut report = validators::run_validators(&registry, &context, &team);
+        assert_that!(report.health_check).is_empty();
+        report.health_check = team.health_check();
+        let actual = process_team_outcomes(vec![TeamOutcome::Checked(Box::new(report.clone()))]);
+
+        assert_that!(report.health_check).has_length(11);
+        assert_that!(
+            report
+                .health_check
+                .iter()
+                .all(|player| player.is_starting())
+        )
+        .is_true();
+        assert_that!(actual).contains("Health check:\n- ");
+        assert_that!(actual).contains("João Pedro is suspended: Suspended until 31 Aug");
+    }
+
+    #[test]
+    fn should_load_rulebook_from_toml_file() {
+        let actual = Rulebook::from_file(Path::new("tests/samples/rulebook.toml"))
+            .expect("Something went wrong loading rulebook");
+
+        assert_that!(actual.price_limit.enabled).is_true();
+        assert_that!(actual.price_limit.price_limit_in_millions).is_equal_to(14.5);
+        assert_that!(actual.club_limit.max_players_per_club).is_equal_to(3);
+        assert_that!(actual.club_limit.exempt_chips)
+            .is_equal_to(vec![Chip::Wildcard, Chip::FreeHit]);
+        assert_that!(actual.promoted_clubs.enabled).is_false();
+        assert_that!(actual.promoted_clubs.club_ids).is_equal_to(vec![3]);
+    }
+
+    #[test]
+    fn should_load_position_limits_and_formation_from_rulebook() {
+        let actual = Rulebook::from_file(Path::new("tests/samples/rulebook_positions.toml"))
+            .expect("Something went wrong loading rulebook");
+
+        assert_that!(actual.position_limits[0].id.clone())
+            .is_equal_to("premium_forwards".to_string());
+        assert_that!(actual.position_limits[0].position).is_equal_to(Position::Forward);
+        assert_that!(actual.position_limits[0].max_players).is_equal_to(Some(1));
+        assert_that!(actual.position_limits[1].id.clone()).is_equal_to("back_five".to_string());
+        assert_that!(actual.position_limits[1].scope).is_equal_to(RuleScope::StartingXi);
+        assert_that!(actual.formation.enabled).is_true();
+        assert_that!(
+            RuleRegistry::from_rulebook(&actual)
+                .describe()
+                .iter()
+                .map(|rule| rule.description.clone())
+                .collect::<Vec<String>>()[3..]
+                .to_vec()
+        )
+        .is_equal_to(vec![
+            "At most 1 forward costing 8m or more".to_string(),
+            "At least 5 defenders".to_string(),
+            "Starting XI must field a legal formation".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn should_load_rulebook_from_json_file_with_defaults_for_missing_rules() {
+        let actual = Rulebook::from_file(Path::new("tests/samples/rulebook.json"))
+            .expect("Something went wrong loading rulebook");
+
+        assert_that!(actual.price_limit.enabled).is_false();
+        assert_that!(actual.club_limit).is_equal_to(Rulebook::default().club_limit);
+        assert_that!(actual.promoted_clubs).is_equal_to(Rulebook::default().promoted_clubs);
+    }
+
+    #[test]
+    fn should_reject_unknown_rulebook_fields() {
+        let typo: Result<Rulebook, toml::de::Error> =
+            toml::from_str("[price_limt]\nprice_limit_in_millions = 9.0\n");
+        let misspelt_setting: Result<Rulebook, toml::de::Error> =
+            toml::from_str("[price_limit]\nprice_limit_in_million = 9.0\n");
+
+        assert_that!(typo.is_err()).is_true();
+        assert_that!(misspelt_setting.is_err()).is_true();
+    }
+
+    #[test]
+    fn should_reject_position_limits_without_their_own_id() {
+        let missing_id: Result<Rulebook, toml::de::Error> =
+            toml::from_str("[[position_limits]]\nposition = \"FWD\"\nmax_players = 1\n");
+        let rulebook_dir = write_snapshots(
+            "duplicate_position_limit_ids",
+            &[
+                (
+                    "rulebook.toml",
+                    "[[position_limits]]\nid = \"forwards\"\nposition = \"FWD\"\nmax_players = 1\n\n\
+                 [[position_limits]]\nid = \"forwards\"\nposition = \"FWD\"\nmin_players = 1\n",
+                ),
+                (
+                    "built_in_id.toml",
+                    "[[position_limits]]\nid = \"price_limit\"\nposition = \"FWD\"\nmax_players = 1\n",
+                ),
+            ],
+        );
+
+        let duplicate_id = Rulebook::from_file(&rulebook_dir.join("rulebook.toml"))
+            .expect_err("Something went wrong: duplicate position limit ids loaded");
+        let built_in_id = Rulebook::from_file(&rulebook_dir.join("built_in_id.toml"))
+            .expect_err("Something went wrong: position limit with a built-in id loaded");
+
+        assert_that!(missing_id.is_err()).is_true();
+        assert_that!(
+            duplicate_id
+                .to_string()
+                .contains("forwards is used more than once")
+        )
+        .is_true();
+        assert_that!(
+            built_in_id
+                .to_string()
+                .contains("already a built-in rule's")
+        )
+        .is_true();
+        let _ = std::fs::remove_dir_all(rulebook_dir);
+    }
+
+    #[test]
+    fn should_reject_h2h_league_with_other_teams() {
+        let arguments = |arguments: &[&str]| -> Vec<String> {
+            arguments
+                .iter()
+                .map(|argument| argument.to_string())
+                .collect()
+        };
+
+        let with_team = parse_cli_options(&arguments(&["--h2h-league", "5", "123"]));
+        let with_league = parse_cli_options(&arguments(&["--h2h-league", "5", "--league", "7"]));
+        let for_ledger = parse_cli_options(&arguments(&["--ledger", "--h2h-league", "5", "123"]));
+
+        assert_that!(with_team.is_err()).is_true();
+        assert_that!(with_league.is_err()).is_true();
+        assert_that!(for_ledger.is_ok()).is_true();
+    }
+
+    #[test]
+    fn should_reject_penalties_for_unknown_rules() {
+        let rulebook_dir = write_snapshots(
+            "unknown_penalty",
+            &[
+                (
+                    "typo.toml",
+                    "[penalties.price_limt]\nkind = \"flat\"\npoints = 4\n",
+                ),
+                (
+                    "position_limit.toml",
+                    "[[position_limits]]\nid = \"premium_forwards\"\nposition = \"FWD\"\n\
+                     max_players = 1\n\n[penalties.premium_forwards]\nkind = \"flat\"\npoints = 4\n",
+                ),
+            ],
+        );
+
+        let typo = Rulebook::from_file(&rulebook_dir.join("typo.toml"))
+            .expect_err("Something went wrong: penalty for an unknown rule loaded");
+        let position_limit = Rulebook::from_file(&rulebook_dir.join("position_limit.toml"))
+            .expect("Something went wrong loading a position limit penalty");
+
+        assert_that!(typo.to_string().contains("unknown rule price_limt")).is_true();
+        assert_that!(typo.exit_code()).is_equal_to(2);
+        assert_that!(position_limit.penalties.contains_key("premium_forwards")).is_true();
+        let _ = std::fs::remove_dir_all(rulebook_dir);
+    }
+
+    #[test]
+    fn should_list_every_built_in_rule_id() {
+        let mut rulebook = Rulebook::default();
+        rulebook.price_limit.enabled = true;
+        rulebook.club_limit.enabled = true;
+        rulebook.promoted_clubs.enabled = true;
+        rulebook.captain_price_limit.enabled = true;
+        rulebook.captain_clubs.enabled = true;
+        rulebook.repeat_captain.enabled = true;
+        rulebook.formation.enabled = true;
+        rulebook.hit_limit.enabled = true;
+        rulebook.promoted_club_transfers.enabled = true;
+        rulebook.squad_value_limit.enabled = true;
+        rulebook.minimum_bank.enabled = true;
+
+        let mut actual: Vec<String> = RuleRegistry::from_rulebook(&rulebook)
+            .describe()
+            .into_iter()
+            .map(|rule| rule.id)
+            .collect();
+        actual.sort();
+        let mut expected: Vec<String> = rulebook::BUILT_IN_RULE_IDS
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        expected.sort();
+
+        assert_that!(actual).is_equal_to(expected);
+    }
+
+    #[test]
+    fn should_fail_to_load_missing_rulebook_with_usage_exit_code() {
+        let actual = Rulebook::from_file(Path::new("tests/samples/no_such_rulebook.toml"))
+            .expect_err("Something went wrong: missing rulebook loaded");
+
+        assert_that!(matches!(actual, FplCheckerError::Rulebook(_))).is_true();
+        assert_that!(actual.exit_code()).is_equal_to(2);
+    }
+
+    #[test]
+    fn should_only_run_validators_enabled_in_rulebook() {
+        let rulebook = Rulebook::from_file(Path::new("tests/samples/rulebook.toml"))
+            .expect("Something went wrong loading rulebook");
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let team = from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        let report = validators::run_validators(
+            &RuleRegistry::from_rulebook(&rulebook),
+            &RuleContext {
+                clubs_by_club_id: &clubs_by_club_id,
+                play_limits_by_position: &IndexMap::new(),
+            },
             &team,
         );
+        let violations = report.violations();
+
+        assert_that!(violations.is_empty()).is_true()
+    }
+
+    #[test]
+    fn should_fail_if_team_has_more_players_from_a_club_than_configured_limit() {
+        let team = from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        let actual = team_contains_at_most_n_players_per_club(&team, 2);
+
+        assert_that!(actual.message)
+            .contains("has more than 2 players from Arsenal (Gabriel, Saliba and Gyökeres)");
+    }
+
+    #[test]
+    fn should_register_rules_enabled_in_rulebook_in_order() {
+        let rulebook = Rulebook::from_file(Path::new("tests/samples/rulebook.toml"))
+            .expect("Something went wrong loading rulebook");
+        let actual: Vec<String> = RuleRegistry::from_rulebook(&rulebook)
+            .describe()
+            .into_iter()
+            .map(|rule| rule.id)
+            .collect();
+
+        assert_that!(actual).is_equal_to(vec!["price_limit".to_string(), "club_limit".to_string()]);
+    }
+
+    #[test]
+    fn should_evaluate_house_rules_registered_alongside_rulebook_rules() {
+        struct NoCaptainFromBurnley;
+
+        impl Rule for NoCaptainFromBurnley {
+            fn id(&self) -> &'static str {
+                "no_captain_from_burnley"
+            }
+
+            fn description(&self) -> String {
+                "Captain must not play for Burnley".to_string()
+            }
+
+            fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+                if team.captain.club.id == 3 {
+                    return ValidationResult::invalid(team, "captained a Burnley player");
+                }
+                ValidationResult::valid(team)
+            }
+        }
+
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let mut team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        team.captain.club.id = 3;
+        let mut registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        registry.register(NoCaptainFromBurnley, Severity::Warning);
+        let report = validators::run_validators(
+            &registry,
+            &RuleContext {
+                clubs_by_club_id: &clubs_by_club_id,
+                play_limits_by_position: &IndexMap::new(),
+            },
+            &team,
+        );
+        let violations = report.violations();
+
+        assert_that!(violations.len()).is_equal_to(1);
+        assert_that!(violations[0].rule_id).is_equal_to("no_captain_from_burnley".to_string());
+        assert_that!(violations[0].severity).is_equal_to(Severity::Warning);
+        assert_that!(violations[0].message).is_equal_to("captained a Burnley player".to_string());
+    }
+
+    #[test]
+    fn should_keep_reports_for_each_team_separate() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let invalid_team: Team =
+            from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+        let mut valid_team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        valid_team.id = 1;
+
+        let invalid_report = validators::run_validators(&registry, &context, &invalid_team);
+        let valid_report = validators::run_validators(&registry, &context, &valid_team);
+        let actual = outcomes_by_team_id(vec![
+            TeamOutcome::Checked(Box::new(invalid_report.clone())),
+            TeamOutcome::Checked(Box::new(valid_report.clone())),
+        ]);
+
+        assert_that!(actual.keys().copied().collect::<Vec<i64>>())
+            .is_equal_to(vec![invalid_team.id, 1]);
+        assert_that!(invalid_report.violations().is_empty()).is_false();
+        assert_that!(valid_report.violations().is_empty()).is_true();
+        assert_that!(valid_report.results.len()).is_equal_to(3);
+    }
+
+    #[test]
+    fn should_print_a_section_per_manager() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let invalid_team: Team =
+            from_str(INVALID_TEAM_JSON).expect("Something went wrong parsing invalid team");
+        let valid_team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let actual = process_team_outcomes(vec![
+            TeamOutcome::Checked(Box::new(validators::run_validators(
+                &registry,
+                &context,
+                &invalid_team,
+            ))),
+            TeamOutcome::Checked(Box::new(validators::run_validators(
+                &registry,
+                &context,
+                &valid_team,
+            ))),
+        ]);
+
+        assert_that!(actual)
+            .contains(format!("== {} ({}) ==", invalid_team.name, invalid_team.owner).as_str());
+        assert_that!(actual).contains(
+            format!(
+                "== {} ({}) ==\nNo rules broken",
+                valid_team.name, valid_team.owner
+            )
+            .as_str(),
+        );
+        assert_that!(actual).contains("Team value 100.0m (4.0m in the bank)");
+    }
+
+    #[test]
+    fn should_report_teams_that_could_not_be_checked_alongside_others() {
+        let valid_team: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+
+        let actual = process_team_outcomes(vec![
+            TeamOutcome::Failed {
+                team_id: 42,
+                error: FplCheckerError::HttpStatus(404),
+            },
+            TeamOutcome::Checked(Box::new(validators::run_validators(
+                &registry,
+                &context,
+                &valid_team,
+            ))),
+        ]);
+
+        assert_that!(actual)
+            .contains("== Team 42 ==\nCould not check: FPL API responded with status 404");
+        assert_that!(actual).contains(
+            format!(
+                "== {} ({}) ==\nNo rules broken",
+                valid_team.name, valid_team.owner
+            )
+            .as_str(),
+        );
+    }
+
+    #[test]
+    fn should_fail_to_build_team_with_unknown_player() {
+        let bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        let gameweek_data: GameweekData =
+            from_str(GAMEWEEK_JSON).expect("Something went wrong parsing gameweek data");
+        let mut picks_data: PicksData =
+            from_str(PICKS_JSON).expect("Something went wrong parsing picks data");
+        picks_data.picks[0].element = 999_999;
+
+        let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
+        let players_by_player_id = build_players_by_id(&clubs_by_club_id, &bootstrap_data)
+            .expect("Something went wrong building players");
+        let actual =
+            build_team_from_data(2239760, &players_by_player_id, &gameweek_data, &picks_data);
+
+        assert_that!(actual).is_equal_to(Err(FplCheckerError::UnknownPlayer(999_999)));
+    }
+
+    #[test]
+    fn should_fail_to_find_current_gameweek_between_seasons() {
+        let mut bootstrap_data: BootstrapData =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        for event in &mut bootstrap_data.events {
+            event.is_current = false;
+        }
+
+        let actual = get_current_gameweek(&bootstrap_data);
+
+        assert_that!(actual).is_equal_to(Err(FplCheckerError::NoCurrentGameweek));
+    }
+
+    #[test]
+    fn should_map_errors_to_http_status_and_exit_codes() {
+        assert_that!(FplCheckerError::InvalidTeamId(0).status()).is_equal_to(Status::BadRequest);
+        assert_that!(FplCheckerError::HttpStatus(404).status()).is_equal_to(Status::NotFound);
+        assert_that!(FplCheckerError::Network("timed out".to_string()).status())
+            .is_equal_to(Status::BadGateway);
+        assert_that!(FplCheckerError::NoCurrentGameweek.status())
+            .is_equal_to(Status::ServiceUnavailable);
+        assert_that!(FplCheckerError::InvalidTeamId(0).exit_code()).is_equal_to(2);
+        assert_that!(FplCheckerError::NoCurrentGameweek.exit_code()).is_equal_to(5);
+        assert_that!(FplCheckerError::Storage("locked".to_string()).status())
+            .is_equal_to(Status::InternalServerError);
+    }
+
+    #[tokio::test]
+    async fn should_validate_teams_from_offline_snapshots() {
+        let offline_dir = write_snapshots(
+            "offline",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/1/picks.json", PICKS_JSON),
+            ],
+        );
+        let expected: Team =
+            from_str(VALID_TEAM_JSON).expect("Something went wrong parsing valid team");
+
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let actual = run_validation_for_teams(
+            TeamsRequest {
+                teams: vec![1, 2239760, 1],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong validating offline");
+
+        assert_that!(
+            actual
+                .iter()
+                .map(TeamOutcome::team_id)
+                .collect::<Vec<i64>>()
+        )
+        .is_equal_to(vec![1, 2239760, 1]);
+        match &actual[1] {
+            TeamOutcome::Checked(report) => {
+                assert_that!(report.team).is_equal_to(&expected);
+                assert_that!(report.violations().is_empty()).is_true();
+            }
+            TeamOutcome::Failed { error, .. } => panic!("Offline check failed: {}", error),
+        }
+        assert_that!(matches!(
+            &actual[0],
+            TeamOutcome::Failed {
+                team_id: 1,
+                error: FplCheckerError::Io(_)
+            }
+        ))
+        .is_true();
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_only_health_check_the_current_gameweek_and_later() {
+        let mut bootstrap: serde_json::Value =
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+        for element in bootstrap["elements"]
+            .as_array_mut()
+            .expect("Something went wrong reading bootstrap elements")
+        {
+            element["status"] = "s".into();
+            element["news"] = "Suspended until 31 Aug".into();
+        }
+        for event in bootstrap["events"]
+            .as_array_mut()
+            .expect("Something went wrong reading bootstrap events")
+        {
+            event["is_current"] = (event["id"] == 2).into();
+        }
+        let offline_dir = write_snapshots(
+            "health_check",
+            &[
+                ("bootstrap-static.json", &bootstrap.to_string()),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/1/picks.json", PICKS_JSON),
+                ("entry/2239760/event/2/picks.json", PICKS_JSON),
+                ("entry/2239760/event/3/picks.json", PICKS_JSON),
+            ],
+        );
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let mut reports: Vec<TeamReport> = Vec::new();
+
+        for gameweek in [None, Some(2), Some(3), Some(1)] {
+            let outcomes = run_validation_for_teams(
+                TeamsRequest {
+                    teams: vec![2239760],
+                    gameweek,
+                    ..TeamsRequest::default()
+                },
+                &registry,
+                &client,
+                None,
+                "test",
+            )
+            .await
+            .expect("Something went wrong validating offline");
+            match outcomes.into_iter().next() {
+                Some(TeamOutcome::Checked(report)) => reports.push(*report),
+                _ => panic!("Something went wrong checking gameweek {:?}", gameweek),
+            }
+        }
+
+        assert_that!(reports[0].health_check).has_length(11);
+        assert_that!(reports[1].health_check).has_length(11);
+        assert_that!(reports[2].health_check).has_length(11);
+        assert_that!(reports[3].health_check).is_empty();
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_only_fetch_transfers_when_a_rule_needs_them() {
+        let snapshot_files = [
+            ("bootstrap-static.json", BOOTSTRAP_JSON),
+            ("entry/2239760.json", GAMEWEEK_JSON),
+            ("entry/2239760/event/2/picks.json", PICKS_JSON),
+        ];
+        let without_transfers = write_snapshots("without_transfers", &snapshot_files);
+        let with_transfers = write_snapshots(
+            "with_transfers",
+            &[
+                snapshot_files.as_slice(),
+                &[("entry/2239760/transfers.json", TRANSFERS_JSON)],
+            ]
+            .concat(),
+        );
+        let mut rulebook = Rulebook::default();
+        rulebook.price_limit.price_basis = PriceBasis::Purchase;
+        let purchase_registry = RuleRegistry::from_rulebook(&rulebook);
+        let default_registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let request = TeamsRequest {
+            gameweek: Some(2),
+            teams: vec![2239760],
+            ..TeamsRequest::default()
+        };
+        let bowen_purchase_price = |outcomes: &[TeamOutcome]| match &outcomes[0] {
+            TeamOutcome::Checked(report) => report
+                .team
+                .players
+                .iter()
+                .find(|player| player.id == 624)
+                .and_then(|player| player.purchase_price_in_millions),
+            TeamOutcome::Failed { error, .. } => panic!("Offline check failed: {}", error),
+        };
+
+        let mut actual = Vec::new();
+        for (offline_dir, registry) in [
+            (&with_transfers, &default_registry),
+            (&with_transfers, &purchase_registry),
+            (&without_transfers, &purchase_registry),
+        ] {
+            let client = FplClient::new().with_offline_dir(Some(offline_dir));
+            let outcomes =
+                run_validation_for_teams(request.clone(), registry, &client, None, "test")
+                    .await
+                    .expect("Something went wrong validating offline");
+            actual.push(bowen_purchase_price(&outcomes));
+        }
+
+        assert_that!(default_registry.needs_transfers()).is_false();
+        assert_that!(purchase_registry.needs_transfers()).is_true();
+        assert_that!(actual).is_equal_to(vec![None, Some(7.8), None]);
+
+        let _ = std::fs::remove_dir_all(&without_transfers);
+        let _ = std::fs::remove_dir_all(&with_transfers);
+    }
+
+    #[tokio::test]
+    async fn should_validate_picks_for_requested_gameweek() {
+        let offline_dir = write_snapshots(
+            "gameweek",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/5/picks.json", PICKS_JSON),
+            ],
+        );
+
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let actual = run_validation_for_teams(
+            TeamsRequest {
+                gameweek: Some(5),
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong validating gameweek 5");
+        let out_of_range = run_validation_for_teams(
+            TeamsRequest {
+                gameweek: Some(0),
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await;
+
+        assert_that!(matches!(&actual[0], TeamOutcome::Checked(_))).is_true();
+        assert_that!(out_of_range).is_equal_to(Err(FplCheckerError::InvalidGameweek(0)));
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_flag_captaining_the_same_player_in_consecutive_gameweeks() {
+        let offline_dir = write_snapshots(
+            "repeat_captain",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/4/picks.json", PICKS_JSON),
+                ("entry/2239760/event/5/picks.json", PICKS_JSON),
+            ],
+        );
+        let mut rulebook = Rulebook::default();
+        rulebook.repeat_captain.enabled = true;
+        let registry = RuleRegistry::from_rulebook(&rulebook);
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+
+        let actual = run_validation_for_teams(
+            TeamsRequest {
+                gameweek: Some(5),
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong validating gameweek 5");
 
-        assert_that!(violations).is_empty()
+        assert_that!(registry.needs_previous_captain()).is_true();
+        match &actual[0] {
+            TeamOutcome::Checked(report) => {
+                assert_that!(report.team.previous_captain_id).is_equal_to(Some(249));
+                let repeat_captain = report
+                    .violations()
+                    .into_iter()
+                    .find(|violation| violation.rule_id == "repeat_captain")
+                    .expect("Repeat captain not flagged");
+                assert_that!(
+                    repeat_captain
+                        .message
+                        .contains("has captained João Pedro two gameweeks running")
+                )
+                .is_true();
+            }
+            TeamOutcome::Failed { error, .. } => panic!("Gameweek 5 check failed: {}", error),
+        }
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_check_every_member_of_a_classic_league_across_pages() {
+        let offline_dir = write_snapshots(
+            "league",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                (
+                    "leagues-classic/314/standings-page_standings-1.json",
+                    r#"{"standings": {"has_next": true, "page": 1, "results": [{"entry": 2239760}, {"entry": 5}]}}"#,
+                ),
+                (
+                    "leagues-classic/314/standings-page_standings-2.json",
+                    r#"{"standings": {"has_next": false, "page": 2, "results": [{"entry": 7}]}}"#,
+                ),
+            ],
+        );
+
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+        let actual = run_validation_for_teams(
+            TeamsRequest {
+                teams: vec![2239760],
+                league: Some(314),
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong validating league");
+
+        assert_that!(
+            actual
+                .iter()
+                .map(TeamOutcome::team_id)
+                .collect::<Vec<i64>>()
+        )
+        .is_equal_to(vec![2239760, 5, 7]);
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_record_fetched_payloads_in_a_layout_offline_mode_can_replay() {
+        let offline_dir = write_snapshots(
+            "record_source",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/1/picks.json", PICKS_JSON),
+            ],
+        );
+        let record_dir = write_snapshots("record_archive", &[]);
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+
+        let recording_client = FplClient::new()
+            .with_offline_dir(Some(&offline_dir))
+            .with_recorder(Some(SnapshotRecorder::new(&record_dir)));
+        let recorded = run_validation_for_teams(
+            TeamsRequest {
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &recording_client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong recording");
+
+        let archive_dir = std::fs::read_dir(record_dir.join("gameweek-1"))
+            .expect("Gameweek archive not created")
+            .next()
+            .expect("Timestamped archive not created")
+            .expect("Something went wrong reading archive")
+            .path();
+        let replay_client = FplClient::new().with_offline_dir(Some(&archive_dir));
+        let replayed = run_validation_for_teams(
+            TeamsRequest {
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &replay_client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong replaying archive");
+
+        assert_that!(
+            archive_dir
+                .join("entry/2239760/event/1/picks.json")
+                .exists()
+        )
+        .is_true();
+        assert_that!(replayed).is_equal_to(recorded);
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+        let _ = std::fs::remove_dir_all(&record_dir);
+    }
+
+    #[tokio::test]
+    async fn should_keep_violations_from_each_run_in_history() {
+        let offline_dir = write_snapshots(
+            "history_run",
+            &[
+                ("bootstrap-static.json", BOOTSTRAP_JSON),
+                ("entry/2239760.json", GAMEWEEK_JSON),
+                ("entry/2239760/event/1/picks.json", PICKS_JSON),
+            ],
+        );
+        let history_path = offline_dir.join("history.db");
+        let mut rulebook = Rulebook::default();
+        rulebook.price_limit.price_limit_in_millions = 9.0;
+        let registry = RuleRegistry::from_rulebook(&rulebook);
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let history =
+            ViolationHistory::open(&history_path).expect("Something went wrong opening history");
+
+        let mut violations_per_run = Vec::new();
+        for _ in 0..2 {
+            let outcomes = run_validation_for_teams(
+                TeamsRequest {
+                    teams: vec![2239760],
+                    ..TeamsRequest::default()
+                },
+                &registry,
+                &client,
+                Some(&history),
+                "test",
+            )
+            .await
+            .expect("Something went wrong validating offline");
+            violations_per_run.push(
+                outcomes
+                    .iter()
+                    .map(|outcome| match outcome {
+                        TeamOutcome::Checked(report) => report.violations().len(),
+                        TeamOutcome::Failed { .. } => 0,
+                    })
+                    .sum::<usize>(),
+            );
+        }
+
+        let records = query_history(Some(&history), &[2239760], Some(1))
+            .expect("Something went wrong querying history");
+        let price_limit_records = records
+            .iter()
+            .filter(|record| record.rule_id == "price_limit")
+            .count();
+        assert_that!(price_limit_records).is_greater_than(0);
+        assert_that!(violations_per_run[0]).is_equal_to(violations_per_run[1]);
+        assert_that!(records.len()).is_equal_to(violations_per_run[0]);
+        assert_that!(matches!(
+            query_history(None, &[2239760], None),
+            Err(FplCheckerError::Storage(_))
+        ))
+        .is_true();
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
     }
 
     #[ignore]
     #[test]
     fn team_to_json() {
         let bootstrap_data: BootstrapData =
-            from_str(&BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
+            from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap data");
         let gameweek_data: GameweekData =
-            from_str(&GAMEWEEK_JSON).expect("Something went wrong parsing gameweek data");
+            from_str(GAMEWEEK_JSON).expect("Something went wrong parsing gameweek data");
 
-        let picks_data: PicksData = fetch_data_as_json(&format!(
-            "https://fantasy.premierleague.com/api/entry/{}/event/{}/picks/",
-            866231, 2
-        ))
-        .expect("Something went wrong parsing picks data");
+        let picks_data: PicksData = FplClient::new()
+            .fetch_data_as_json(&format!(
+                "https://fantasy.premierleague.com/api/entry/{}/event/{}/picks/",
+                866231, 2
+            ))
+            .expect("Something went wrong parsing picks data");
 
         let clubs_by_club_id = build_clubs_by_id(&bootstrap_data);
-        let players_by_player_id = build_players_by_id(&clubs_by_club_id, &bootstrap_data);
+        let players_by_player_id = build_players_by_id(&clubs_by_club_id, &bootstrap_data)
+            .expect("Something went wrong building players");
 
         let team =
-            build_team_from_data(4402816, &players_by_player_id, &gameweek_data, &picks_data);
+            build_team_from_data(4402816, &players_by_player_id, &gameweek_data, &picks_data)
+                .expect("Something went wrong building team");
 
         println!(
             "{}",
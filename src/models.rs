@@ -1,3 +1,5 @@
+use crate::errors::FplCheckerError;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
@@ -10,6 +12,8 @@ pub struct BootstrapTeam {
 pub struct BootstrapEvent {
     pub id: i64,
     pub is_current: bool,
+    #[serde(default)]
+    pub finished: bool,
 }
 
 #[derive(Deserialize)]
@@ -17,54 +21,422 @@ pub struct BootstrapElement {
     pub id: i64,
     pub web_name: String,
     pub now_cost: f64,
+    #[serde(default)]
+    pub cost_change_start: f64,
     pub team: i64,
+    #[serde(default)]
+    pub element_type: i64,
+    #[serde(default)]
+    pub status: PlayerStatus,
+    #[serde(default)]
+    pub news: String,
+    #[serde(default)]
+    pub chance_of_playing_next_round: Option<i64>,
+}
+
+/// A playing position, with how many of it a starting XI may field.
+#[derive(Deserialize)]
+pub struct BootstrapElementType {
+    pub id: i64,
+    pub singular_name_short: String,
+    pub squad_min_play: usize,
+    pub squad_max_play: usize,
 }
 
 #[derive(Deserialize)]
 pub struct BootstrapData {
     pub elements: Vec<BootstrapElement>,
+    #[serde(default)]
+    pub element_types: Vec<BootstrapElementType>,
     pub events: Vec<BootstrapEvent>,
     pub teams: Vec<BootstrapTeam>,
 }
 
+/// A player's per-fixture history from `/api/element-summary/{id}/`, used for historical prices.
+#[derive(Deserialize)]
+pub struct ElementSummary {
+    pub history: Vec<ElementHistory>,
+}
+
+#[derive(Deserialize)]
+pub struct ElementHistory {
+    pub round: i64,
+    pub value: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LeagueType {
+    Classic,
+    HeadToHead,
+}
+
+impl LeagueType {
+    pub fn path(&self) -> &'static str {
+        match self {
+            LeagueType::Classic => "leagues-classic",
+            LeagueType::HeadToHead => "leagues-h2h",
+        }
+    }
+}
+
+/// A page of `/api/leagues-classic/{id}/standings/` or `/api/leagues-h2h/{id}/standings/`.
+#[derive(Deserialize)]
+pub struct LeagueStandingsData {
+    pub standings: LeagueStandings,
+}
+
+#[derive(Deserialize)]
+pub struct LeagueStandings {
+    pub has_next: bool,
+    pub results: Vec<LeagueStanding>,
+}
+
+#[derive(Deserialize)]
+pub struct LeagueStanding {
+    pub entry: i64,
+}
+
+/// What a manager scored in a gameweek and the points docked for the rules they broke.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct GameweekPenalties {
+    /// Points scored less any hits taken, matching what FPL adds to the season total.
+    pub points: i64,
+    pub transfer_cost: i64,
+    pub penalty_points: i64,
+    pub adjusted_points: i64,
+    pub broken_rules: Vec<String>,
+}
+
+/// A manager's row in the adjusted league table, with the gameweeks their score came from.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct LedgerRow {
+    pub rank: usize,
+    pub team_id: i64,
+    pub name: String,
+    pub owner: String,
+    pub points: i64,
+    pub penalty_points: i64,
+    pub adjusted_points: i64,
+    pub gameweeks: IndexMap<i64, GameweekPenalties>,
+}
+
+/// A manager's place in the league once their season total has had penalties taken off.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct StandingsRow {
+    pub rank: usize,
+    pub team_id: i64,
+    pub name: String,
+    pub owner: String,
+    pub overall_points: i64,
+    pub penalty_points: i64,
+    pub adjusted_points: i64,
+}
+
+/// A page of `/api/leagues-h2h-matches/league/{id}/`.
+#[derive(Deserialize)]
+pub struct H2hMatchesData {
+    pub has_next: bool,
+    pub results: Vec<H2hMatch>,
+}
+
+/// A head-to-head fixture. `entry_2_entry` is missing when a manager plays the league average,
+/// and `winner` when the fixture was drawn.
+#[derive(Deserialize)]
+pub struct H2hMatch {
+    pub entry_1_entry: i64,
+    pub entry_2_entry: Option<i64>,
+    pub winner: Option<i64>,
+}
+
+/// A head-to-head fixture checked against the rules, flagging wins by a team that broke them.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct H2hFixture {
+    pub entry_1: i64,
+    pub entry_2: Option<i64>,
+    pub winner: Option<i64>,
+    pub winner_broken_rules: Vec<String>,
+}
+
+/// Every team in a head-to-head league checked for a gameweek, alongside that gameweek's fixtures.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct H2hCheck {
+    pub gameweek: i64,
+    pub teams: IndexMap<i64, TeamOutcome>,
+    pub fixtures: Vec<H2hFixture>,
+}
+
+/// A transfer from `/api/entry/{id}/transfers/`, with costs in tenths of a million.
+#[derive(Deserialize)]
+pub struct TransferData {
+    pub element_in: i64,
+    pub element_in_cost: f64,
+    #[serde(default)]
+    pub element_out: i64,
+    #[serde(default)]
+    pub element_out_cost: f64,
+    pub event: i64,
+}
+
 #[derive(Deserialize)]
 pub struct PicksData {
+    #[serde(default)]
+    pub active_chip: Option<Chip>,
+    pub entry_history: EntryHistory,
     pub picks: Vec<PickElement>,
 }
 
+/// The manager's results for the gameweek the picks were made for.
+#[derive(Deserialize)]
+pub struct EntryHistory {
+    pub points: i64,
+    #[serde(default)]
+    pub event_transfers: i64,
+    /// Points spent on hits, 4 for each transfer over the free ones.
+    #[serde(default)]
+    pub event_transfers_cost: i64,
+    /// Money in the bank, in tenths of a million.
+    #[serde(default)]
+    pub bank: Option<i64>,
+    /// Team value including the bank, in tenths of a million.
+    #[serde(default)]
+    pub value: Option<i64>,
+}
+
+/// A pick in the manager's lineup. Positions 1-11 start and 12-15 are the bench in order;
+/// `multiplier` is 0 for a benched player, 2 for the captain and 3 for a triple captain.
 #[derive(Deserialize)]
 pub struct PickElement {
     pub is_captain: bool,
+    #[serde(default)]
+    pub is_vice_captain: bool,
     pub element: i64,
+    pub position: i64,
+    pub multiplier: i64,
 }
 
 #[derive(Deserialize)]
 pub struct GameweekData {
-    pub current_event: i64,
     pub name: String,
+    #[serde(default)]
+    pub summary_overall_points: i64,
     pub player_first_name: String,
+    /// The gameweek the manager picked their first squad for.
+    #[serde(default)]
+    pub started_event: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    #[default]
+    Violation,
+}
+
+/// A chip played for the gameweek, named as the FPL API names it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Chip {
+    #[serde(rename = "wildcard")]
+    Wildcard,
+    #[serde(rename = "freehit")]
+    FreeHit,
+    #[serde(rename = "bboost")]
+    BenchBoost,
+    #[serde(rename = "3xc")]
+    TripleCaptain,
+    #[serde(rename = "manager")]
+    AssistantManager,
+    /// A chip this checker doesn't know about yet, so a new one doesn't stop teams being checked.
+    #[serde(other, rename = "other")]
+    Other,
+}
+
+/// Whether a player is fit to play, named as the FPL API's element `status` names it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PlayerStatus {
+    #[default]
+    #[serde(rename = "a")]
+    Available,
+    #[serde(rename = "d")]
+    Doubtful,
+    #[serde(rename = "i")]
+    Injured,
+    #[serde(rename = "s")]
+    Suspended,
+    #[serde(rename = "u")]
+    Unavailable,
+    #[serde(rename = "n")]
+    NotInSquad,
+    #[serde(other, rename = "other")]
+    Other,
+}
+
+impl PlayerStatus {
+    pub fn description(&self) -> &'static str {
+        match self {
+            PlayerStatus::Available => "available",
+            PlayerStatus::Doubtful => "doubtful",
+            PlayerStatus::Injured => "injured",
+            PlayerStatus::Suspended => "suspended",
+            PlayerStatus::NotInSquad => "not in the squad",
+            PlayerStatus::Unavailable | PlayerStatus::Other => "unavailable",
+        }
+    }
+}
+
+/// Why a player might not play, for those FPL doesn't list as available.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Availability {
+    pub status: PlayerStatus,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub news: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chance_of_playing_next_round: Option<i64>,
+}
+
+/// A player's position, named by the FPL API's short names.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Position {
+    #[serde(rename = "GKP")]
+    Goalkeeper,
+    #[serde(rename = "DEF")]
+    Defender,
+    #[serde(rename = "MID")]
+    Midfielder,
+    #[serde(rename = "FWD")]
+    Forward,
+}
+
+impl Position {
+    pub fn from_short_name(short_name: &str) -> Option<Self> {
+        match short_name {
+            "GKP" => Some(Position::Goalkeeper),
+            "DEF" => Some(Position::Defender),
+            "MID" => Some(Position::Midfielder),
+            "FWD" => Some(Position::Forward),
+            _ => None,
+        }
+    }
+
+    pub fn plural_name(&self) -> &'static str {
+        match self {
+            Position::Goalkeeper => "goalkeepers",
+            Position::Defender => "defenders",
+            Position::Midfielder => "midfielders",
+            Position::Forward => "forwards",
+        }
+    }
+
+    /// The position's name for `count` players, e.g. "1 forward" but "2 forwards".
+    pub fn name_for(&self, count: usize) -> &'static str {
+        if count == 1 {
+            match self {
+                Position::Goalkeeper => "goalkeeper",
+                Position::Defender => "defender",
+                Position::Midfielder => "midfielder",
+                Position::Forward => "forward",
+            }
+        } else {
+            self.plural_name()
+        }
+    }
+}
+
+/// How many players of a position a starting XI may field, from bootstrap `element_types`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PlayLimits {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Which of a team's players a rule looks at.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleScope {
+    /// The whole 15-man squad.
+    #[default]
+    Squad,
+    /// Only the players that started the gameweek.
+    StartingXi,
+}
+
+/// Which of a player's prices a price cap is enforced against.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceBasis {
+    #[default]
+    Current,
+    Purchase,
+    Selling,
 }
 
 #[derive(Debug, PartialEq, Clone, Default, Serialize)]
 pub struct ValidationResult {
+    pub team_id: i64,
+    pub owner: String,
+    pub rule_id: String,
     pub is_valid: bool,
-    pub reason: String,
+    pub severity: Severity,
+    pub offending_players: Vec<Player>,
+    pub offending_clubs: Vec<Club>,
+    /// How many players over a club's limit the team has, for per-extra-player penalties.
+    pub extra_players: usize,
+    /// Offending players whose penalty counts twice because of the chip played, such as a
+    /// triple-captained player over the price cap.
+    pub chip_penalised_players: usize,
+    pub penalty_points: i64,
+    pub message: String,
 }
 
 impl ValidationResult {
-    pub(crate) fn valid() -> Self {
+    pub(crate) fn valid(team: &Team) -> Self {
         Self {
+            team_id: team.id,
+            owner: team.owner.clone(),
             is_valid: true,
-            reason: "".to_string(),
+            ..Self::default()
         }
     }
 
-    pub(crate) fn invalid(reason: &str) -> Self {
+    pub(crate) fn invalid(team: &Team, message: &str) -> Self {
         Self {
+            team_id: team.id,
+            owner: team.owner.clone(),
             is_valid: false,
-            reason: reason.to_string(),
+            message: message.to_string(),
+            ..Self::default()
         }
     }
+
+    pub(crate) fn with_offending_players(mut self, players: Vec<Player>) -> Self {
+        self.offending_players = players;
+        self
+    }
+
+    pub(crate) fn with_offending_clubs(mut self, clubs: Vec<Club>) -> Self {
+        self.offending_clubs = clubs;
+        self
+    }
+
+    pub(crate) fn with_extra_players(mut self, extra_players: usize) -> Self {
+        self.extra_players = extra_players;
+        self
+    }
+
+    pub(crate) fn with_chip_penalised_players(mut self, chip_penalised_players: usize) -> Self {
+        self.chip_penalised_players = chip_penalised_players;
+        self
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct RuleDescription {
+    pub id: String,
+    pub description: String,
+    pub severity: Severity,
+    pub scope: RuleScope,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exempt_chips: Vec<Chip>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
@@ -79,6 +451,42 @@ pub struct Player {
     pub name: String,
     pub price_in_millions: f64,
     pub club: Club,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_price_in_millions: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selling_price_in_millions: Option<f64>,
+    /// Where the manager picked the player in their lineup, 1-11 starting and 12-15 on the bench.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineup_slot: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Availability>,
+}
+
+impl Player {
+    /// The player's price on `basis`, falling back to the current price where the manager's
+    /// purchase history isn't known.
+    pub fn price_for(&self, basis: PriceBasis) -> f64 {
+        match basis {
+            PriceBasis::Current => self.price_in_millions,
+            PriceBasis::Purchase => self
+                .purchase_price_in_millions
+                .unwrap_or(self.price_in_millions),
+            PriceBasis::Selling => self
+                .selling_price_in_millions
+                .unwrap_or(self.price_in_millions),
+        }
+    }
+
+    /// Whether the player started: picked in the first eleven and scoring. A player with no
+    /// lineup information is treated as starting.
+    pub fn is_starting(&self) -> bool {
+        self.lineup_slot.is_none_or(|slot| slot <= 11)
+            && self.multiplier.is_none_or(|multiplier| multiplier > 0)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -86,12 +494,167 @@ pub struct Team {
     pub id: i64,
     pub name: String,
     pub owner: String,
+    #[serde(default)]
+    pub points: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_chip: Option<Chip>,
     pub captain: Player,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vice_captain: Option<Player>,
+    /// Who the manager captained the gameweek before, when a rule needs to know.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_captain_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "GameweekTransfers::is_empty")]
+    pub gameweek_transfers: GameweekTransfers,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank_in_millions: Option<f64>,
+    /// Team value as FPL reports it, which includes the bank.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_in_millions: Option<f64>,
     pub players: Vec<Player>,
 }
 
-#[derive(Deserialize, Debug)]
+/// A transfer made for the gameweek, with the player bought at what they cost and the player
+/// sold at what the manager got for them.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Transfer {
+    pub player_in: Player,
+    pub player_out: Player,
+}
+
+/// The transfers a manager made going into a gameweek and what they cost in points.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct GameweekTransfers {
+    pub count: i64,
+    pub points_cost: i64,
+    pub transfers: Vec<Transfer>,
+}
+
+impl GameweekTransfers {
+    pub fn is_empty(&self) -> bool {
+        self.count == 0 && self.points_cost == 0 && self.transfers.is_empty()
+    }
+}
+
+impl Team {
+    /// What the squad itself is worth: team value less the bank.
+    pub fn squad_value_in_millions(&self) -> Option<f64> {
+        Some(self.value_in_millions? - self.bank_in_millions.unwrap_or(0.0))
+    }
+
+    /// Starters and the captain who are injured, suspended, doubtful or otherwise unavailable.
+    pub fn health_check(&self) -> Vec<Player> {
+        let mut players: Vec<Player> = self
+            .players
+            .iter()
+            .filter(|player| player.is_starting())
+            .cloned()
+            .collect();
+        if !players.iter().any(|player| player.id == self.captain.id) {
+            players.push(self.captain.clone());
+        }
+
+        players.retain(|player| player.availability.is_some());
+        players
+    }
+
+    /// The team narrowed to the players a rule with `scope` looks at.
+    pub fn in_scope(&self, scope: RuleScope) -> Team {
+        match scope {
+            RuleScope::Squad => self.clone(),
+            RuleScope::StartingXi => Team {
+                players: self
+                    .players
+                    .iter()
+                    .filter(|player| player.is_starting())
+                    .cloned()
+                    .collect(),
+                ..self.clone()
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct TeamReport {
+    pub team: Team,
+    pub results: Vec<ValidationResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub health_check: Vec<Player>,
+}
+
+impl TeamReport {
+    pub fn violations(&self) -> Vec<&ValidationResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.is_valid)
+            .collect()
+    }
+
+    pub fn penalty_points(&self) -> i64 {
+        self.results
+            .iter()
+            .map(|result| result.penalty_points)
+            .sum()
+    }
+}
+
+/// A rule a team broke in a gameweek, as kept in the violation history.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct ViolationRecord {
+    pub gameweek: i64,
+    pub team_id: i64,
+    pub team_name: String,
+    pub owner: String,
+    pub rule_id: String,
+    pub severity: Severity,
+    pub offending_players: Vec<String>,
+    pub penalty_points: i64,
+    pub message: String,
+    /// When the violation was first recorded, in RFC 3339.
+    pub recorded_at: String,
+}
+
+/// The outcome of checking a single team: either its report, or the error that stopped it being
+/// checked while the remaining teams carried on.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum TeamOutcome {
+    Checked(Box<TeamReport>),
+    Failed {
+        team_id: i64,
+        error: FplCheckerError,
+    },
+}
+
+impl TeamOutcome {
+    pub fn team_id(&self) -> i64 {
+        match self {
+            TeamOutcome::Checked(report) => report.team.id,
+            TeamOutcome::Failed { team_id, .. } => *team_id,
+        }
+    }
+}
+
+/// A manager's outcome for every finished gameweek of the season, keyed by gameweek.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct SeasonAudit {
+    pub team_id: i64,
+    /// The manager's official season total, if their entry could be fetched.
+    pub overall_points: Option<i64>,
+    pub gameweeks: IndexMap<i64, TeamOutcome>,
+}
+
+/// The teams to check: those listed, plus every member of `league` and `h2h_league` if given.
+#[derive(Deserialize, Debug, Clone, Default)]
 #[serde(crate = "rocket::serde")]
 pub struct TeamsRequest {
+    #[serde(default)]
     pub teams: Vec<i64>,
+    #[serde(default)]
+    pub league: Option<i64>,
+    #[serde(default)]
+    pub h2h_league: Option<i64>,
+    #[serde(default)]
+    pub gameweek: Option<i64>,
 }
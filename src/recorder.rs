@@ -0,0 +1,83 @@
+use crate::api::relative_path_for_uri;
+use crate::cache::write_file;
+use crate::errors::FplCheckerError;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+use time::macros::format_description;
+
+/// Archives every raw FPL payload fetched during a run under
+/// `<root>/gameweek-<gw>/<timestamp>/`, using the same layout `--offline` reads from.
+#[derive(Debug, Clone)]
+pub struct SnapshotRecorder {
+    root: PathBuf,
+    session: Arc<Mutex<RecordingSession>>,
+}
+
+#[derive(Debug, Default)]
+struct RecordingSession {
+    started_at: String,
+    archive_dir: Option<PathBuf>,
+    pending: Vec<(PathBuf, String)>,
+}
+
+impl SnapshotRecorder {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            session: Arc::new(Mutex::new(RecordingSession {
+                started_at: timestamp(),
+                ..RecordingSession::default()
+            })),
+        }
+    }
+
+    /// Starts a fresh archive for a new run. Payloads are held in memory until the gameweek
+    /// being checked is known.
+    pub fn start_session(&self) -> Self {
+        Self::new(&self.root)
+    }
+
+    /// Fixes the archive directory for this session and writes out anything recorded so far.
+    pub fn archive_gameweek(&self, gameweek: i64) -> Result<PathBuf, FplCheckerError> {
+        let mut session = self.lock_session()?;
+        let archive_dir = self
+            .root
+            .join(format!("gameweek-{}", gameweek))
+            .join(&session.started_at);
+
+        for (path, body) in session.pending.drain(..) {
+            write_file(&archive_dir.join(path), &body)?;
+        }
+        session.archive_dir = Some(archive_dir.clone());
+
+        Ok(archive_dir)
+    }
+
+    pub fn record(&self, uri: &str, body: &str) -> Result<(), FplCheckerError> {
+        let path = relative_path_for_uri(uri)?;
+        let mut session = self.lock_session()?;
+
+        match &session.archive_dir {
+            Some(archive_dir) => write_file(&archive_dir.join(path), body),
+            None => {
+                session.pending.push((path, body.to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    fn lock_session(&self) -> Result<std::sync::MutexGuard<'_, RecordingSession>, FplCheckerError> {
+        self.session
+            .lock()
+            .map_err(|_| FplCheckerError::Io("Snapshot recorder is unavailable".to_string()))
+    }
+}
+
+fn timestamp() -> String {
+    OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .unwrap_or_default()
+}
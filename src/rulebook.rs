@@ -0,0 +1,406 @@
+use crate::constants::{
+    DEFAULT_MAX_HITS, DEFAULT_MAX_PLAYERS_PER_CLUB, DEFAULT_MIN_BANK_IN_MILLIONS,
+    DEFAULT_PRICE_LIMIT_IN_MILLIONS, DEFAULT_SQUAD_VALUE_LIMIT_IN_MILLIONS, NEWLY_PROMOTED_CLUBS,
+    RULEBOOK_PATH_ENV,
+};
+use crate::errors::FplCheckerError;
+use crate::models::{Chip, Position, PriceBasis, RuleScope, Severity, ValidationResult};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The ids of the rulebook's own rules, which penalties and reports use to tell them apart.
+pub const BUILT_IN_RULE_IDS: [&str; 11] = [
+    "price_limit",
+    "club_limit",
+    "promoted_clubs",
+    "captain_price_limit",
+    "captain_clubs",
+    "repeat_captain",
+    "formation",
+    "hit_limit",
+    "promoted_club_transfers",
+    "squad_value_limit",
+    "minimum_bank",
+];
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rulebook {
+    pub price_limit: PriceLimitRule,
+    pub club_limit: ClubLimitRule,
+    pub promoted_clubs: PromotedClubsRule,
+    pub captain_price_limit: CaptainPriceLimitRule,
+    pub captain_clubs: CaptainClubsRule,
+    pub repeat_captain: RepeatCaptainRule,
+    pub position_limits: Vec<PositionLimitRule>,
+    pub formation: FormationRule,
+    pub hit_limit: HitLimitRule,
+    pub promoted_club_transfers: PromotedClubTransfersRule,
+    pub squad_value_limit: SquadValueLimitRule,
+    pub minimum_bank: MinimumBankRule,
+    pub penalties: IndexMap<String, Penalty>,
+}
+
+/// Points docked for breaking a rule, keyed by rule id in the rulebook's `penalties` table.
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Penalty {
+    pub kind: PenaltyKind,
+    pub points: i64,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PenaltyKind {
+    /// The same deduction however badly the rule was broken.
+    Flat,
+    /// A deduction for each offending player.
+    PerPlayer,
+    /// A deduction for each player over a club's limit.
+    PerExtraPlayer,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriceLimitRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub price_limit_in_millions: f64,
+    pub price_basis: PriceBasis,
+    pub scope: RuleScope,
+    pub exempt_chips: Vec<Chip>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClubLimitRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub max_players_per_club: usize,
+    pub scope: RuleScope,
+    pub exempt_chips: Vec<Chip>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromotedClubsRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub club_ids: Vec<i64>,
+    pub scope: RuleScope,
+    /// Chips whose squads don't have to follow the rule. A free hit squad only lasts a week, so
+    /// it's exempt by default.
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Caps what the captain (and the vice-captain, if included) may cost.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CaptainPriceLimitRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub price_limit_in_millions: f64,
+    pub include_vice_captain: bool,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Restricts the captain (and the vice-captain, if included) to players from the listed clubs.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CaptainClubsRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub club_ids: Vec<i64>,
+    pub include_vice_captain: bool,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Stops a manager captaining the same player in consecutive gameweeks.
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepeatCaptainRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Bounds how many players of a position a team has, optionally only counting those at or above
+/// a price, e.g. at most one forward costing 8m or more. Listed under `[[position_limits]]`, each
+/// with its own `id` so penalties and reports can tell them apart.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PositionLimitRule {
+    pub id: String,
+    #[serde(default)]
+    pub severity: Severity,
+    pub position: Position,
+    #[serde(default)]
+    pub min_price_in_millions: Option<f64>,
+    #[serde(default)]
+    pub min_players: usize,
+    #[serde(default)]
+    pub max_players: Option<usize>,
+    #[serde(default)]
+    pub scope: RuleScope,
+    #[serde(default)]
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Checks the starting XI fields a formation FPL allows, using the limits in bootstrap
+/// `element_types`.
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormationRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Caps how many -4 hits a manager may take in a single gameweek.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct HitLimitRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub max_hits: i64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Makes a manager who sells a player from one of the listed clubs buy one from another of them.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromotedClubTransfersRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub club_ids: Vec<i64>,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Caps what a squad is worth, not counting money in the bank.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SquadValueLimitRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub max_value_in_millions: f64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+/// Makes a manager keep at least some money in the bank.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MinimumBankRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    pub min_bank_in_millions: f64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Default for PriceLimitRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Violation,
+            price_limit_in_millions: DEFAULT_PRICE_LIMIT_IN_MILLIONS,
+            price_basis: PriceBasis::Current,
+            scope: RuleScope::Squad,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for CaptainPriceLimitRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            price_limit_in_millions: DEFAULT_PRICE_LIMIT_IN_MILLIONS,
+            include_vice_captain: false,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for CaptainClubsRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            club_ids: NEWLY_PROMOTED_CLUBS.to_vec(),
+            include_vice_captain: false,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for HitLimitRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            max_hits: DEFAULT_MAX_HITS,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for PromotedClubTransfersRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            club_ids: NEWLY_PROMOTED_CLUBS.to_vec(),
+            exempt_chips: vec![Chip::FreeHit],
+        }
+    }
+}
+
+impl Default for SquadValueLimitRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            max_value_in_millions: DEFAULT_SQUAD_VALUE_LIMIT_IN_MILLIONS,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for MinimumBankRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            severity: Severity::Violation,
+            min_bank_in_millions: DEFAULT_MIN_BANK_IN_MILLIONS,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for ClubLimitRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Violation,
+            max_players_per_club: DEFAULT_MAX_PLAYERS_PER_CLUB,
+            scope: RuleScope::Squad,
+            exempt_chips: Vec::new(),
+        }
+    }
+}
+
+impl Default for PromotedClubsRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Violation,
+            club_ids: NEWLY_PROMOTED_CLUBS.to_vec(),
+            scope: RuleScope::Squad,
+            exempt_chips: vec![Chip::FreeHit],
+        }
+    }
+}
+
+impl Penalty {
+    pub fn points_for(&self, result: &ValidationResult) -> i64 {
+        if result.is_valid {
+            return 0;
+        }
+
+        // A chip that makes a player count for more makes breaking the rule with them cost more.
+        let multiplier = match self.kind {
+            PenaltyKind::Flat if result.chip_penalised_players > 0 => 2,
+            PenaltyKind::Flat => 1,
+            PenaltyKind::PerPlayer => {
+                result.offending_players.len() + result.chip_penalised_players
+            }
+            PenaltyKind::PerExtraPlayer => result.extra_players + result.chip_penalised_players,
+        };
+
+        self.points * multiplier as i64
+    }
+}
+
+impl Rulebook {
+    pub fn from_file(path: &Path) -> Result<Self, FplCheckerError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| FplCheckerError::Rulebook(format!("{}: {}", path.display(), error)))?;
+
+        let rulebook: Self = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|error| {
+                FplCheckerError::Rulebook(format!("{}: {}", path.display(), error))
+            })?,
+            Some("json") => serde_json::from_str(&contents).map_err(|error| {
+                FplCheckerError::Rulebook(format!("{}: {}", path.display(), error))
+            })?,
+            _ => {
+                return Err(FplCheckerError::Rulebook(format!(
+                    "unsupported format {} (expected .toml or .json)",
+                    path.display()
+                )));
+            }
+        };
+
+        rulebook
+            .check_position_limit_ids()
+            .and_then(|_| rulebook.check_penalty_ids())
+            .map_err(|error| FplCheckerError::Rulebook(format!("{}: {}", path.display(), error)))?;
+        Ok(rulebook)
+    }
+
+    /// Position limits are told apart by `id` in penalties and reports, so no two may share one
+    /// and none may take a built-in rule's.
+    fn check_position_limit_ids(&self) -> Result<(), String> {
+        let mut ids: Vec<&str> = Vec::new();
+
+        for position_limit in &self.position_limits {
+            if BUILT_IN_RULE_IDS.contains(&position_limit.id.as_str()) {
+                return Err(format!(
+                    "position limit id {} is already a built-in rule's",
+                    position_limit.id
+                ));
+            }
+            if ids.contains(&position_limit.id.as_str()) {
+                return Err(format!(
+                    "position limit id {} is used more than once",
+                    position_limit.id
+                ));
+            }
+            ids.push(&position_limit.id);
+        }
+
+        Ok(())
+    }
+
+    /// A penalty keyed by a rule id that doesn't exist, like a typo, would never dock points.
+    fn check_penalty_ids(&self) -> Result<(), String> {
+        for rule_id in self.penalties.keys() {
+            let is_known = BUILT_IN_RULE_IDS.contains(&rule_id.as_str())
+                || self
+                    .position_limits
+                    .iter()
+                    .any(|position_limit| &position_limit.id == rule_id);
+
+            if !is_known {
+                return Err(format!("penalty for unknown rule {}", rule_id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the rulebook from `path` if one was given, otherwise from the file named by the
+    /// rulebook environment variable, falling back to the built-in league rules.
+    pub fn load(path: Option<&str>) -> Result<Self, FplCheckerError> {
+        let path = match path {
+            Some(path) => Some(path.to_string()),
+            None => std::env::var(RULEBOOK_PATH_ENV).ok(),
+        };
+
+        match path {
+            Some(path) => Self::from_file(Path::new(&path)),
+            None => Ok(Self::default()),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::models::{
+    Chip, Club, PlayLimits, Position, RuleDescription, RuleScope, Severity, Team, ValidationResult,
+};
+use crate::rulebook::{Penalty, Rulebook};
+use crate::validators::{
+    CaptainClubs, CaptainPriceLimit, ClubLimit, Formation, HitLimit, MinimumBank, PositionLimit,
+    PriceLimit, PromotedClubTransfers, PromotedClubs, RepeatCaptain, SquadValueLimit,
+};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// Shared data a rule may need beyond the team itself.
+pub struct RuleContext<'a> {
+    pub clubs_by_club_id: &'a HashMap<i64, Club>,
+    pub play_limits_by_position: &'a IndexMap<Position, PlayLimits>,
+}
+
+pub trait Rule: Send + Sync {
+    fn id(&self) -> &str;
+
+    fn description(&self) -> String;
+
+    fn evaluate(&self, team: &Team, context: &RuleContext) -> ValidationResult;
+
+    /// Chips whose squads the rule doesn't apply to. Behaviour that changes with a chip, rather
+    /// than being switched off, belongs in `evaluate`.
+    fn exempt_chips(&self) -> &[Chip] {
+        &[]
+    }
+
+    /// Which of the team's players the rule is evaluated against.
+    fn scope(&self) -> RuleScope {
+        RuleScope::Squad
+    }
+
+    /// Whether the rule looks at `Team::previous_captain_id`, which costs an extra fetch per team.
+    fn needs_previous_captain(&self) -> bool {
+        false
+    }
+
+    /// Whether the rule looks at prices or moves from the manager's transfer history, which costs
+    /// an extra fetch per team.
+    fn needs_transfers(&self) -> bool {
+        false
+    }
+}
+
+struct RegisteredRule {
+    rule: Box<dyn Rule>,
+    severity: Severity,
+}
+
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<RegisteredRule>,
+    penalties: IndexMap<String, Penalty>,
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers every rule enabled in the rulebook, in the order they are reported.
+    pub fn from_rulebook(rulebook: &Rulebook) -> Self {
+        let mut registry = Self {
+            penalties: rulebook.penalties.clone(),
+            ..Self::new()
+        };
+
+        if rulebook.price_limit.enabled {
+            registry.register(
+                PriceLimit {
+                    price_limit_in_millions: rulebook.price_limit.price_limit_in_millions,
+                    price_basis: rulebook.price_limit.price_basis,
+                    exempt_chips: rulebook.price_limit.exempt_chips.clone(),
+                    scope: rulebook.price_limit.scope,
+                },
+                rulebook.price_limit.severity,
+            );
+        }
+        if rulebook.promoted_clubs.enabled {
+            registry.register(
+                PromotedClubs {
+                    club_ids: rulebook.promoted_clubs.club_ids.clone(),
+                    exempt_chips: rulebook.promoted_clubs.exempt_chips.clone(),
+                    scope: rulebook.promoted_clubs.scope,
+                },
+                rulebook.promoted_clubs.severity,
+            );
+        }
+        if rulebook.club_limit.enabled {
+            registry.register(
+                ClubLimit {
+                    max_players_per_club: rulebook.club_limit.max_players_per_club,
+                    exempt_chips: rulebook.club_limit.exempt_chips.clone(),
+                    scope: rulebook.club_limit.scope,
+                },
+                rulebook.club_limit.severity,
+            );
+        }
+
+        if rulebook.captain_price_limit.enabled {
+            registry.register(
+                CaptainPriceLimit {
+                    price_limit_in_millions: rulebook.captain_price_limit.price_limit_in_millions,
+                    include_vice_captain: rulebook.captain_price_limit.include_vice_captain,
+                    exempt_chips: rulebook.captain_price_limit.exempt_chips.clone(),
+                },
+                rulebook.captain_price_limit.severity,
+            );
+        }
+        if rulebook.captain_clubs.enabled {
+            registry.register(
+                CaptainClubs {
+                    club_ids: rulebook.captain_clubs.club_ids.clone(),
+                    include_vice_captain: rulebook.captain_clubs.include_vice_captain,
+                    exempt_chips: rulebook.captain_clubs.exempt_chips.clone(),
+                },
+                rulebook.captain_clubs.severity,
+            );
+        }
+        if rulebook.repeat_captain.enabled {
+            registry.register(
+                RepeatCaptain {
+                    exempt_chips: rulebook.repeat_captain.exempt_chips.clone(),
+                },
+                rulebook.repeat_captain.severity,
+            );
+        }
+        for position_limit in &rulebook.position_limits {
+            registry.register(
+                PositionLimit {
+                    id: position_limit.id.clone(),
+                    position: position_limit.position,
+                    min_price_in_millions: position_limit.min_price_in_millions,
+                    min_players: position_limit.min_players,
+                    max_players: position_limit.max_players,
+                    exempt_chips: position_limit.exempt_chips.clone(),
+                    scope: position_limit.scope,
+                },
+                position_limit.severity,
+            );
+        }
+        if rulebook.formation.enabled {
+            registry.register(
+                Formation {
+                    exempt_chips: rulebook.formation.exempt_chips.clone(),
+                },
+                rulebook.formation.severity,
+            );
+        }
+        if rulebook.hit_limit.enabled {
+            registry.register(
+                HitLimit {
+                    max_hits: rulebook.hit_limit.max_hits,
+                    exempt_chips: rulebook.hit_limit.exempt_chips.clone(),
+                },
+                rulebook.hit_limit.severity,
+            );
+        }
+        if rulebook.promoted_club_transfers.enabled {
+            registry.register(
+                PromotedClubTransfers {
+                    club_ids: rulebook.promoted_club_transfers.club_ids.clone(),
+                    exempt_chips: rulebook.promoted_club_transfers.exempt_chips.clone(),
+                },
+                rulebook.promoted_club_transfers.severity,
+            );
+        }
+        if rulebook.squad_value_limit.enabled {
+            registry.register(
+                SquadValueLimit {
+                    max_value_in_millions: rulebook.squad_value_limit.max_value_in_millions,
+                    exempt_chips: rulebook.squad_value_limit.exempt_chips.clone(),
+                },
+                rulebook.squad_value_limit.severity,
+            );
+        }
+        if rulebook.minimum_bank.enabled {
+            registry.register(
+                MinimumBank {
+                    min_bank_in_millions: rulebook.minimum_bank.min_bank_in_millions,
+                    exempt_chips: rulebook.minimum_bank.exempt_chips.clone(),
+                },
+                rulebook.minimum_bank.severity,
+            );
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, rule: impl Rule + 'static, severity: Severity) {
+        self.rules.push(RegisteredRule {
+            rule: Box::new(rule),
+            severity,
+        });
+    }
+
+    pub fn needs_previous_captain(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|registered| registered.rule.needs_previous_captain())
+    }
+
+    pub fn needs_transfers(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|registered| registered.rule.needs_transfers())
+    }
+
+    pub fn describe(&self) -> Vec<RuleDescription> {
+        self.rules
+            .iter()
+            .map(|registered| RuleDescription {
+                id: registered.rule.id().to_string(),
+                description: registered.rule.description(),
+                severity: registered.severity,
+                scope: registered.rule.scope(),
+                exempt_chips: registered.rule.exempt_chips().to_vec(),
+            })
+            .collect()
+    }
+
+    /// Evaluates every rule against the team, stamping each result with the rule that produced it
+    /// and the points it costs. Rules exempt for the chip the team played pass without being run, and
+    /// the rest only see the players in their scope.
+    pub fn evaluate(&self, team: &Team, context: &RuleContext) -> Vec<ValidationResult> {
+        self.rules
+            .iter()
+            .map(|registered| {
+                let is_exempt = team
+                    .active_chip
+                    .is_some_and(|chip| registered.rule.exempt_chips().contains(&chip));
+                let result = if is_exempt {
+                    ValidationResult::valid(team)
+                } else {
+                    registered
+                        .rule
+                        .evaluate(&team.in_scope(registered.rule.scope()), context)
+                };
+                let penalty_points = self
+                    .penalties
+                    .get(registered.rule.id())
+                    .map(|penalty| penalty.points_for(&result))
+                    .unwrap_or_default();
+
+                ValidationResult {
+                    rule_id: registered.rule.id().to_string(),
+                    severity: registered.severity,
+                    penalty_points,
+                    ..result
+                }
+            })
+            .collect()
+    }
+}
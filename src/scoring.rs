@@ -0,0 +1,324 @@
+use crate::models::{GameweekPenalties, LedgerRow, SeasonAudit, StandingsRow, TeamOutcome};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// Totals each manager's gameweek points, after hits, and penalties into a table ranked by
+/// adjusted points. Gameweeks that couldn't be checked are left out, as their points aren't known.
+pub fn build_adjusted_table(audits: &[SeasonAudit]) -> Vec<LedgerRow> {
+    let mut rows: Vec<LedgerRow> = audits
+        .iter()
+        .map(|audit| {
+            let mut name = format!("Team {}", audit.team_id);
+            let mut owner = String::new();
+            let mut gameweeks = IndexMap::new();
+
+            for (gameweek, outcome) in &audit.gameweeks {
+                let TeamOutcome::Checked(report) = outcome else {
+                    continue;
+                };
+                let penalty_points = report.penalty_points();
+                let transfer_cost = report.team.gameweek_transfers.points_cost;
+                let points = report.team.points - transfer_cost;
+
+                name = report.team.name.clone();
+                owner = report.team.owner.clone();
+                gameweeks.insert(
+                    *gameweek,
+                    GameweekPenalties {
+                        points,
+                        transfer_cost,
+                        penalty_points,
+                        adjusted_points: points - penalty_points,
+                        broken_rules: report
+                            .violations()
+                            .iter()
+                            .map(|violation| violation.rule_id.clone())
+                            .collect(),
+                    },
+                );
+            }
+
+            let points = gameweeks.values().map(|gameweek| gameweek.points).sum();
+            let penalty_points = gameweeks
+                .values()
+                .map(|gameweek| gameweek.penalty_points)
+                .sum();
+
+            LedgerRow {
+                rank: 0,
+                team_id: audit.team_id,
+                name,
+                owner,
+                points,
+                penalty_points,
+                adjusted_points: points - penalty_points,
+                gameweeks,
+            }
+        })
+        .collect();
+
+    rows.sort_by_key(|row| std::cmp::Reverse(row.adjusted_points));
+    let ranks = competition_ranks(rows.iter().map(|row| row.adjusted_points));
+    for (row, rank) in rows.iter_mut().zip(ranks) {
+        row.rank = rank;
+    }
+
+    rows
+}
+
+/// Ranks managers on their official season total (`summary_overall_points`) less every penalty
+/// they've picked up. Managers whose entry couldn't be fetched count as zero points.
+pub fn build_adjusted_standings(audits: &[SeasonAudit]) -> Vec<StandingsRow> {
+    let overall_points_by_team_id: HashMap<i64, i64> = audits
+        .iter()
+        .map(|audit| (audit.team_id, audit.overall_points.unwrap_or_default()))
+        .collect();
+    let mut rows: Vec<StandingsRow> = build_adjusted_table(audits)
+        .into_iter()
+        .map(|row| {
+            let overall_points = overall_points_by_team_id[&row.team_id];
+
+            StandingsRow {
+                rank: 0,
+                team_id: row.team_id,
+                name: row.name,
+                owner: row.owner,
+                overall_points,
+                penalty_points: row.penalty_points,
+                adjusted_points: overall_points - row.penalty_points,
+            }
+        })
+        .collect();
+
+    rows.sort_by_key(|row| std::cmp::Reverse(row.adjusted_points));
+    let ranks = competition_ranks(rows.iter().map(|row| row.adjusted_points));
+    for (row, rank) in rows.iter_mut().zip(ranks) {
+        row.rank = rank;
+    }
+
+    rows
+}
+
+/// Ranks points already sorted highest first, with tied managers sharing a rank.
+fn competition_ranks(points: impl Iterator<Item = i64>) -> Vec<usize> {
+    let mut ranks: Vec<usize> = Vec::new();
+    let mut previous_points = None;
+
+    for (index, points) in points.enumerate() {
+        let rank = match (previous_points, ranks.last()) {
+            (Some(previous_points), Some(previous_rank)) if previous_points == points => {
+                *previous_rank
+            }
+            _ => index + 1,
+        };
+        ranks.push(rank);
+        previous_points = Some(points);
+    }
+
+    ranks
+}
+
+pub fn process_adjusted_standings(rows: &[StandingsRow]) -> String {
+    let mut output = String::from("== Adjusted standings ==\n");
+
+    for row in rows {
+        output.push_str(&format!(
+            "{}. {} ({}): {} - {} = {}\n",
+            row.rank,
+            row.name,
+            row.owner,
+            row.overall_points,
+            row.penalty_points,
+            row.adjusted_points
+        ));
+    }
+
+    output
+}
+
+pub fn process_adjusted_table(rows: &[LedgerRow]) -> String {
+    let mut output = String::from("== Adjusted table ==\n");
+
+    for row in rows {
+        output.push_str(&format!(
+            "{}. {} ({}): {} pts - {} penalty = {}\n",
+            row.rank, row.name, row.owner, row.points, row.penalty_points, row.adjusted_points
+        ));
+
+        for (gameweek, penalties) in &row.gameweeks {
+            if penalties.penalty_points > 0 {
+                output.push_str(&format!(
+                    "   GW{}: -{} ({})\n",
+                    gameweek,
+                    penalties.penalty_points,
+                    penalties.broken_rules.join(", ")
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Team, TeamReport};
+    use crate::rulebook::Rulebook;
+    use crate::rules::{RuleContext, RuleRegistry};
+    use crate::validators::run_validators;
+    use assertor::*;
+
+    const INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON: &str =
+        include_str!("../tests/samples/invalid_team_many_players_many_clubs.json");
+
+    fn registry_with_penalties() -> RuleRegistry {
+        let rulebook: Rulebook = toml::from_str(
+            r#"
+            [promoted_clubs]
+            enabled = false
+
+            [penalties.price_limit]
+            kind = "per_player"
+            points = 4
+
+            [penalties.club_limit]
+            kind = "per_extra_player"
+            points = 2
+            "#,
+        )
+        .expect("Something went wrong parsing rulebook");
+
+        RuleRegistry::from_rulebook(&rulebook)
+    }
+
+    fn report_for(team: &Team) -> TeamReport {
+        let clubs_by_club_id = HashMap::new();
+        let context = RuleContext {
+            clubs_by_club_id: &clubs_by_club_id,
+            play_limits_by_position: &IndexMap::new(),
+        };
+
+        run_validators(&registry_with_penalties(), &context, team)
+    }
+
+    #[test]
+    fn should_dock_points_per_player_over_each_club_limit() {
+        let team: Team = serde_json::from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+
+        let report = report_for(&team);
+        let club_limit = report
+            .results
+            .iter()
+            .find(|result| result.rule_id == "club_limit")
+            .expect("Club limit not evaluated");
+
+        // Three from Arsenal, two each from Chelsea and Man Utd
+        assert_that!(club_limit.extra_players).is_equal_to(4);
+        assert_that!(club_limit.penalty_points).is_equal_to(8);
+    }
+
+    #[test]
+    fn should_rank_managers_by_points_after_penalties() {
+        let mut clean: Team = serde_json::from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        clean.players.truncate(1);
+        clean.id = 1;
+        clean.points = 50;
+        let mut penalised: Team = serde_json::from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        penalised.id = 2;
+        penalised.points = 55;
+        let penalised_report = report_for(&penalised);
+        let penalty_points = penalised_report.penalty_points();
+        let audits = vec![
+            SeasonAudit {
+                team_id: 2,
+                overall_points: Some(110),
+                gameweeks: IndexMap::from([
+                    (1, TeamOutcome::Checked(Box::new(penalised_report.clone()))),
+                    (2, TeamOutcome::Checked(Box::new(penalised_report))),
+                ]),
+            },
+            SeasonAudit {
+                team_id: 1,
+                overall_points: Some(96),
+                gameweeks: IndexMap::from([
+                    (1, TeamOutcome::Checked(Box::new(report_for(&clean)))),
+                    (2, TeamOutcome::Checked(Box::new(report_for(&clean)))),
+                ]),
+            },
+        ];
+
+        let table = build_adjusted_table(&audits);
+
+        assert_that!(table.iter().map(|row| row.team_id).collect::<Vec<i64>>())
+            .is_equal_to(vec![1, 2]);
+        assert_that!(table[1].points).is_equal_to(110);
+        assert_that!(table[1].penalty_points).is_equal_to(2 * penalty_points);
+        assert_that!(table[1].adjusted_points).is_equal_to(110 - 2 * penalty_points);
+        assert_that!(table[1].rank).is_equal_to(2);
+        assert_that!(process_adjusted_table(&table).contains("GW1: -")).is_true();
+    }
+
+    #[test]
+    fn should_take_hits_off_ledger_points() {
+        let mut team: Team = serde_json::from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        team.players.truncate(1);
+        team.points = 60;
+        team.gameweek_transfers.points_cost = 8;
+        let report = report_for(&team);
+        let audits = vec![SeasonAudit {
+            team_id: 1,
+            overall_points: Some(52),
+            gameweeks: IndexMap::from([(1, TeamOutcome::Checked(Box::new(report)))]),
+        }];
+
+        let table = build_adjusted_table(&audits);
+        let standings = build_adjusted_standings(&audits);
+
+        assert_that!(table[0].gameweeks[&1].transfer_cost).is_equal_to(8);
+        assert_that!(table[0].points).is_equal_to(52);
+        assert_that!(table[0].adjusted_points).is_equal_to(standings[0].adjusted_points);
+    }
+
+    #[test]
+    fn should_rank_standings_on_season_total_less_penalties() {
+        let mut team: Team = serde_json::from_str(INVALID_TEAM_MANY_PLAYERS_MANY_CLUBS_JSON)
+            .expect("Something went wrong parsing invalid team");
+        let penalised_report = report_for(&team);
+        team.players.truncate(1);
+        let clean_report = report_for(&team);
+        let audits = vec![
+            SeasonAudit {
+                team_id: 1,
+                overall_points: Some(120),
+                gameweeks: IndexMap::from([(
+                    1,
+                    TeamOutcome::Checked(Box::new(penalised_report.clone())),
+                )]),
+            },
+            SeasonAudit {
+                team_id: 2,
+                overall_points: Some(
+                    120 - penalised_report.penalty_points() + clean_report.penalty_points(),
+                ),
+                gameweeks: IndexMap::from([(1, TeamOutcome::Checked(Box::new(clean_report)))]),
+            },
+        ];
+
+        let standings = build_adjusted_standings(&audits);
+
+        assert_that!(standings[0].adjusted_points).is_equal_to(standings[1].adjusted_points);
+        assert_that!(standings.iter().map(|row| row.rank).collect::<Vec<usize>>())
+            .is_equal_to(vec![1, 1]);
+        let penalised = standings
+            .iter()
+            .find(|row| row.team_id == 1)
+            .expect("Penalised manager missing");
+        assert_that!(penalised.overall_points).is_equal_to(120);
+        assert_that!(penalised.penalty_points).is_equal_to(penalised_report.penalty_points());
+    }
+}
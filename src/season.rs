@@ -0,0 +1,423 @@
+use crate::api::FplClient;
+use crate::builders::{self, build_team_from_data};
+use crate::checker::BootstrapLookups;
+use crate::errors::FplCheckerError;
+use crate::history::ViolationHistory;
+use crate::models::{
+    ElementSummary, GameweekData, PicksData, SeasonAudit, TeamOutcome, TeamsRequest, TransferData,
+};
+use crate::rules::RuleRegistry;
+use crate::validators;
+use indexmap::IndexMap;
+use std::collections::{BTreeSet, HashMap};
+
+type SeasonPicks = (
+    GameweekData,
+    Option<Vec<TransferData>>,
+    Vec<(i64, Result<PicksData, FplCheckerError>)>,
+);
+
+/// Runs every rule against each manager's picks for every finished gameweek, pricing players at
+/// what they cost in that gameweek rather than now. Violations are kept in `history` if given.
+pub async fn run_season_audit(
+    request: TeamsRequest,
+    registry: &RuleRegistry,
+    client: &FplClient,
+    history: Option<&ViolationHistory>,
+    source: &str,
+) -> Result<Vec<SeasonAudit>, FplCheckerError> {
+    let client = client.start_session();
+    let team_ids = client.fetch_team_ids(&request).await?;
+    eprintln!("Auditing season for teams {:?} from {}", team_ids, source);
+    let lookups = BootstrapLookups::fetch(&client).await?;
+    let gameweeks = builders::get_finished_gameweeks(&lookups.bootstrap_data);
+    let context = lookups.context();
+
+    eprintln!("Auditing gameweeks {:?}...", gameweeks);
+    if let Some(latest_gameweek) = gameweeks.last()
+        && let Some(archive_dir) = client.archive_gameweek(*latest_gameweek)?
+    {
+        eprintln!("Recording FPL data to {}", archive_dir.display());
+    }
+
+    let season_gameweeks = gameweeks.clone();
+    let needs_transfers = registry.needs_transfers();
+    let entries = client
+        .fetch_all(team_ids.clone(), move |client, fpl_team_id| {
+            fetch_season_picks(client, fpl_team_id, &season_gameweeks, needs_transfers)
+        })
+        .await;
+
+    let element_ids: BTreeSet<i64> = entries
+        .iter()
+        .flatten()
+        .flat_map(|(_, _, picks_by_gameweek)| picks_by_gameweek)
+        .filter_map(|(_, picks_data)| picks_data.as_ref().ok())
+        .flat_map(|picks_data| &picks_data.picks)
+        .map(|pick| pick.element)
+        .collect();
+    let summaries = client
+        .fetch_all(
+            element_ids.iter().copied().collect(),
+            |client, player_id| client.fetch_element_summary(&player_id),
+        )
+        .await;
+    // A player whose history can't be fetched keeps their current price rather than failing
+    // every team that picked them.
+    let summaries_by_id: HashMap<i64, ElementSummary> = element_ids
+        .into_iter()
+        .zip(summaries)
+        .filter_map(|(player_id, summary)| match summary {
+            Ok(summary) => Some((player_id, summary)),
+            Err(error) => {
+                eprintln!(
+                    "Could not fetch price history for player {}: {}",
+                    player_id, error
+                );
+                None
+            }
+        })
+        .collect();
+    let players_by_gameweek: HashMap<i64, _> = gameweeks
+        .iter()
+        .map(|gameweek| {
+            (
+                *gameweek,
+                builders::build_players_at_gameweek(
+                    &lookups.players_by_id,
+                    &summaries_by_id,
+                    *gameweek,
+                ),
+            )
+        })
+        .collect();
+
+    let mut audits = Vec::new();
+    for (fpl_team_id, entry) in team_ids.into_iter().zip(entries) {
+        let overall_points = entry
+            .as_ref()
+            .ok()
+            .map(|(gameweek_data, _, _)| gameweek_data.summary_overall_points);
+        let outcomes = match entry {
+            Ok((gameweek_data, transfers, picks_by_gameweek)) => {
+                let first_squad_prices = builders::build_first_squad_prices(
+                    &lookups.start_prices,
+                    &summaries_by_id,
+                    gameweek_data.started_event,
+                );
+                let captain_ids: HashMap<i64, i64> = picks_by_gameweek
+                    .iter()
+                    .filter_map(|(gameweek, picks_data)| {
+                        let picks_data = picks_data.as_ref().ok()?;
+                        Some((*gameweek, builders::find_captain_id(picks_data)?))
+                    })
+                    .collect();
+
+                picks_by_gameweek
+                    .into_iter()
+                    .map(|(gameweek, picks_data)| {
+                        let outcome = picks_data
+                            .and_then(|picks_data| {
+                                build_team_from_data(
+                                    fpl_team_id,
+                                    &players_by_gameweek[&gameweek],
+                                    &gameweek_data,
+                                    &picks_data,
+                                )
+                            })
+                            .and_then(|mut team| {
+                                team.previous_captain_id =
+                                    captain_ids.get(&(gameweek - 1)).copied();
+                                let Some(transfers) = &transfers else {
+                                    return Ok(team);
+                                };
+                                let team = builders::apply_transfer_prices(
+                                    team,
+                                    transfers,
+                                    &first_squad_prices,
+                                    gameweek,
+                                );
+                                builders::apply_gameweek_transfers(
+                                    team,
+                                    transfers,
+                                    &players_by_gameweek[&gameweek],
+                                    gameweek,
+                                )
+                            })
+                            .map(|team| {
+                                TeamOutcome::Checked(Box::new(validators::run_validators(
+                                    registry, &context, &team,
+                                )))
+                            })
+                            .unwrap_or_else(|error| TeamOutcome::Failed {
+                                team_id: fpl_team_id,
+                                error,
+                            });
+
+                        (gameweek, outcome)
+                    })
+                    .collect()
+            }
+            Err(error) => {
+                eprintln!("Could not audit team {}: {}", fpl_team_id, error);
+                gameweeks
+                    .iter()
+                    .map(|gameweek| {
+                        (
+                            *gameweek,
+                            TeamOutcome::Failed {
+                                team_id: fpl_team_id,
+                                error: error.clone(),
+                            },
+                        )
+                    })
+                    .collect()
+            }
+        };
+
+        audits.push(SeasonAudit {
+            team_id: fpl_team_id,
+            overall_points,
+            gameweeks: outcomes,
+        });
+    }
+
+    if let Some(history) = history {
+        history.record(audits.iter().flat_map(|audit| {
+            audit
+                .gameweeks
+                .iter()
+                .map(|(gameweek, outcome)| (*gameweek, outcome))
+        }))?;
+    }
+
+    Ok(audits)
+}
+
+/// Fetches a manager's entry once (and their transfers, if a rule needs them), then their picks
+/// for each gameweek. A missing gameweek (for example one before the manager joined) fails on its
+/// own without losing the rest.
+fn fetch_season_picks(
+    client: &FplClient,
+    fpl_team_id: i64,
+    gameweeks: &[i64],
+    needs_transfers: bool,
+) -> Result<SeasonPicks, FplCheckerError> {
+    let gameweek_data = client.fetch_gameweek_data(&fpl_team_id)?;
+    let transfers = if needs_transfers {
+        client.fetch_transfers_if_available(&fpl_team_id)?
+    } else {
+        None
+    };
+    let picks_by_gameweek = gameweeks
+        .iter()
+        .map(|gameweek| (*gameweek, client.fetch_picks(&fpl_team_id, *gameweek)))
+        .collect();
+
+    Ok((gameweek_data, transfers, picks_by_gameweek))
+}
+
+pub fn audits_by_team_id(audits: Vec<SeasonAudit>) -> IndexMap<i64, SeasonAudit> {
+    audits
+        .into_iter()
+        .map(|audit| (audit.team_id, audit))
+        .collect()
+}
+
+/// Prints a section per manager with a line per gameweek listing the rules broken.
+pub fn process_season_audits(audits: &[SeasonAudit]) -> String {
+    let mut output = String::new();
+
+    for audit in audits {
+        let header = audit
+            .gameweeks
+            .values()
+            .find_map(|outcome| match outcome {
+                TeamOutcome::Checked(report) => Some(format!(
+                    "== {} ({}) ==\n",
+                    report.team.name, report.team.owner
+                )),
+                TeamOutcome::Failed { .. } => None,
+            })
+            .unwrap_or_else(|| format!("== Team {} ==\n", audit.team_id));
+        output.push_str(&header);
+
+        if audit.gameweeks.is_empty() {
+            output.push_str("No finished gameweeks\n");
+        }
+        for (gameweek, outcome) in &audit.gameweeks {
+            let line = match outcome {
+                TeamOutcome::Checked(report) => {
+                    let broken_rules: Vec<&str> = report
+                        .violations()
+                        .iter()
+                        .map(|violation| violation.rule_id.as_str())
+                        .collect();
+
+                    if broken_rules.is_empty() {
+                        "No rules broken".to_string()
+                    } else {
+                        broken_rules.join(", ")
+                    }
+                }
+                TeamOutcome::Failed { error, .. } => format!("Could not check: {}", error),
+            };
+            output.push_str(&format!("GW{}: {}\n", gameweek, line));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rulebook::Rulebook;
+    use crate::test_support::write_snapshots;
+    use assertor::*;
+    use serde_json::Value;
+
+    const BOOTSTRAP_JSON: &str = include_str!("../tests/samples/bootstrap.json");
+    const GAMEWEEK_JSON: &str = include_str!("../tests/samples/gameweek.json");
+    const PICKS_JSON: &str = include_str!("../tests/samples/picks.json");
+
+    /// Writes a snapshot directory where gameweeks 1 and 2 are finished, the manager only has
+    /// picks for gameweek 2, and player 249 cost 9.5m in gameweek 2.
+    fn write_season_snapshots(name: &str) -> std::path::PathBuf {
+        let mut bootstrap: Value =
+            serde_json::from_str(BOOTSTRAP_JSON).expect("Something went wrong parsing bootstrap");
+        for event in bootstrap["events"]
+            .as_array_mut()
+            .expect("Bootstrap has no events")
+            .iter_mut()
+            .take(2)
+        {
+            event["finished"] = Value::Bool(true);
+        }
+        let picks: Value =
+            serde_json::from_str(PICKS_JSON).expect("Something went wrong parsing picks");
+
+        let mut files = vec![
+            ("bootstrap-static.json".to_string(), bootstrap.to_string()),
+            ("entry/2239760.json".to_string(), GAMEWEEK_JSON.to_string()),
+            (
+                "entry/2239760/event/2/picks.json".to_string(),
+                PICKS_JSON.to_string(),
+            ),
+        ];
+        for pick in picks["picks"].as_array().expect("Picks missing") {
+            let element = pick["element"].as_i64().expect("Pick has no element");
+            let value = if element == 249 { 95 } else { 50 };
+            files.push((
+                format!("element-summary/{}.json", element),
+                format!(
+                    "{{\"history\": [{{\"round\": 1, \"value\": 140}}, {{\"round\": 2, \"value\": {}}}]}}",
+                    value
+                ),
+            ));
+        }
+
+        write_snapshots(
+            &format!("season_{}", name),
+            &files
+                .iter()
+                .map(|(path, contents)| (path.as_str(), contents.as_str()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_audit_every_finished_gameweek_with_historical_prices() {
+        let offline_dir = write_season_snapshots("audit");
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+
+        let audits = run_season_audit(
+            TeamsRequest {
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong auditing season");
+
+        assert_that!(audits.len()).is_equal_to(1);
+        let gameweeks = &audits[0].gameweeks;
+        assert_that!(gameweeks.keys().copied().collect::<Vec<i64>>()).is_equal_to(vec![1, 2]);
+        assert_that!(matches!(
+            &gameweeks[&1],
+            TeamOutcome::Failed {
+                error: FplCheckerError::Io(_),
+                ..
+            }
+        ))
+        .is_true();
+        match &gameweeks[&2] {
+            TeamOutcome::Checked(report) => {
+                let captain_price = report
+                    .team
+                    .players
+                    .iter()
+                    .find(|player| player.id == 249)
+                    .map(|player| player.price_in_millions);
+                assert_that!(captain_price).is_equal_to(Some(9.5));
+                assert_that!(
+                    report
+                        .violations()
+                        .iter()
+                        .any(|violation| violation.rule_id == "price_limit")
+                )
+                .is_false();
+            }
+            TeamOutcome::Failed { error, .. } => panic!("Gameweek 2 audit failed: {}", error),
+        }
+
+        let output = process_season_audits(&audits);
+        assert_that!(output.contains("GW1: Could not check:")).is_true();
+        assert_that!(output.contains("GW2: ")).is_true();
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+
+    #[tokio::test]
+    async fn should_price_players_without_history_at_their_current_price() {
+        let offline_dir = write_season_snapshots("missing_summary");
+        std::fs::remove_file(offline_dir.join("element-summary/249.json"))
+            .expect("Something went wrong removing element summary");
+        let client = FplClient::new().with_offline_dir(Some(&offline_dir));
+        let registry = RuleRegistry::from_rulebook(&Rulebook::default());
+
+        let audits = run_season_audit(
+            TeamsRequest {
+                teams: vec![2239760],
+                ..TeamsRequest::default()
+            },
+            &registry,
+            &client,
+            None,
+            "test",
+        )
+        .await
+        .expect("Something went wrong auditing season");
+
+        match &audits[0].gameweeks[&2] {
+            TeamOutcome::Checked(report) => {
+                let captain_price = report
+                    .team
+                    .players
+                    .iter()
+                    .find(|player| player.id == 249)
+                    .map(|player| player.price_in_millions);
+                assert_that!(captain_price).is_equal_to(Some(7.5));
+            }
+            TeamOutcome::Failed { error, .. } => panic!("Gameweek 2 audit failed: {}", error),
+        }
+
+        let _ = std::fs::remove_dir_all(&offline_dir);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::cache;
+use std::path::PathBuf;
+
+/// A path in the temp directory named after the test, so tests running at once don't collide.
+/// Tests remove whatever they create there once they're done.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fpl_checker_{}_{}", name, std::process::id()))
+}
+
+/// Writes `files` (paths relative to the snapshot root) into a fresh temp directory named
+/// after the test, for offline mode to read.
+pub fn write_snapshots(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let offline_dir = temp_path(name);
+    let _ = std::fs::remove_dir_all(&offline_dir);
+    for (path, contents) in files {
+        cache::write_file(&offline_dir.join(path), contents)
+            .expect("Something went wrong writing snapshot");
+    }
+
+    offline_dir
+}
@@ -1,15 +1,26 @@
-use crate::constants::{NEWLY_PROMOTED_CLUBS, VIOLATION_PREFIXES};
-use crate::models::{Club, Player, Team, ValidationResult};
+use crate::constants::{POINTS_PER_HIT, VIOLATION_PREFIXES};
+use crate::models::{
+    Chip, Club, PlayLimits, Player, Position, PriceBasis, RuleScope, Team, TeamReport, Transfer,
+    ValidationResult,
+};
+use crate::rules::{Rule, RuleContext, RuleRegistry};
 use indexmap::IndexMap;
 use rand::prelude::IndexedRandom;
 use std::collections::HashMap;
 
-pub fn team_contains_players_under_10_m(team: &Team) -> ValidationResult {
+pub fn team_contains_players_under_price_limit(
+    team: &Team,
+    price_limit_in_millions: f64,
+    price_basis: PriceBasis,
+) -> ValidationResult {
     let mut players_above_price_threshold: IndexMap<String, f64> = IndexMap::new();
+    let mut offending_players: Vec<Player> = Vec::new();
 
     for player in &team.players {
-        if player.price_in_millions >= 10.0 {
-            players_above_price_threshold.insert(player.name.clone(), player.price_in_millions);
+        let price = player.price_for(price_basis);
+        if price >= price_limit_in_millions {
+            players_above_price_threshold.insert(player.name.clone(), price);
+            offending_players.push(player.clone());
         }
     }
 
@@ -29,14 +40,24 @@ pub fn team_contains_players_under_10_m(team: &Team) -> ValidationResult {
         violation_string.push_str(&format!("{} ({}m)", &player_name, price));
     }
 
+    let is_captain_triple_captained = is_captain_triple_captained(team, &offending_players);
+    if is_captain_triple_captained {
+        violation_string.push_str(&format!(", and triple captained {}", team.captain.name));
+    }
+
     if !players_above_price_threshold.is_empty() {
-        return ValidationResult::invalid(&violation_string);
+        return ValidationResult::invalid(team, &violation_string)
+            .with_offending_players(offending_players)
+            .with_chip_penalised_players(usize::from(is_captain_triple_captained));
     }
 
-    ValidationResult::valid()
+    ValidationResult::valid(team)
 }
 
-pub fn team_contains_at_most_one_player_per_club(team: &Team) -> ValidationResult {
+pub fn team_contains_at_most_n_players_per_club(
+    team: &Team,
+    max_players_per_club: usize,
+) -> ValidationResult {
     let mut seen_players_by_club_name: IndexMap<String, Vec<Player>> = IndexMap::new();
 
     for player in &team.players {
@@ -46,7 +67,7 @@ pub fn team_contains_at_most_one_player_per_club(team: &Team) -> ValidationResul
             .push(player.clone());
     }
 
-    seen_players_by_club_name.retain(|_, players| players.len() > 1);
+    seen_players_by_club_name.retain(|_, players| players.len() > max_players_per_club);
 
     let mut violation_string: String = format!(
         "{} {} has",
@@ -56,7 +77,16 @@ pub fn team_contains_at_most_one_player_per_club(team: &Team) -> ValidationResul
         team.owner.clone(),
     );
     for (club_name, players) in &seen_players_by_club_name {
-        violation_string.push_str(&format!(" more than 1 player from {} ", club_name));
+        violation_string.push_str(&format!(
+            " more than {} {} from {} ",
+            max_players_per_club,
+            if max_players_per_club == 1 {
+                "player"
+            } else {
+                "players"
+            },
+            club_name
+        ));
 
         for (index, player) in players.iter().enumerate() {
             if index == 0 {
@@ -75,43 +105,845 @@ pub fn team_contains_at_most_one_player_per_club(team: &Team) -> ValidationResul
     }
 
     if !seen_players_by_club_name.is_empty() {
-        return ValidationResult::invalid(&violation_string);
+        let offending_players: Vec<Player> = seen_players_by_club_name
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        let offending_clubs: Vec<Club> = seen_players_by_club_name
+            .values()
+            .map(|players| players[0].club.clone())
+            .collect();
+
+        let extra_players = seen_players_by_club_name
+            .values()
+            .map(|players| players.len() - max_players_per_club)
+            .sum();
+
+        return ValidationResult::invalid(team, &violation_string)
+            .with_offending_players(offending_players)
+            .with_offending_clubs(offending_clubs)
+            .with_extra_players(extra_players);
     }
 
-    ValidationResult::valid()
+    ValidationResult::valid(team)
 }
 
 pub fn team_contains_players_from_newly_promoted_clubs(
     clubs_by_club_id: &HashMap<i64, Club>,
+    promoted_club_ids: &[i64],
     team: &Team,
 ) -> ValidationResult {
-    for club_id in NEWLY_PROMOTED_CLUBS {
+    for &club_id in promoted_club_ids {
         if !team.players.iter().any(|player| player.club.id == club_id) {
-            return ValidationResult::invalid(&format!(
-                "{} {} has not included players from {}",
-                VIOLATION_PREFIXES.choose(&mut rand::rng()).unwrap(),
-                team.owner,
-                clubs_by_club_id.get(&club_id).unwrap().name
-            ));
+            let club = clubs_by_club_id.get(&club_id).cloned().unwrap_or(Club {
+                id: club_id,
+                name: format!("club {}", club_id),
+            });
+
+            return ValidationResult::invalid(
+                team,
+                &format!(
+                    "{} {} has not included players from {}",
+                    VIOLATION_PREFIXES.choose(&mut rand::rng()).unwrap(),
+                    team.owner,
+                    club.name
+                ),
+            )
+            .with_offending_clubs(vec![club]);
         }
     }
 
-    ValidationResult::valid()
+    ValidationResult::valid(team)
+}
+
+/// The captain, followed by the vice-captain when captaincy rules should cover them too.
+fn captains(team: &Team, include_vice_captain: bool) -> Vec<&Player> {
+    let vice_captain = team.vice_captain.as_ref().filter(|_| include_vice_captain);
+
+    std::iter::once(&team.captain).chain(vice_captain).collect()
+}
+
+/// Whether the captain is among `offending_players` with the triple captain chip played. They
+/// score three times over, so they count twice towards a per-player penalty.
+fn is_captain_triple_captained(team: &Team, offending_players: &[Player]) -> bool {
+    team.active_chip == Some(Chip::TripleCaptain)
+        && offending_players
+            .iter()
+            .any(|player| player.id == team.captain.id)
+}
+
+fn armband(team: &Team, player: &Player) -> &'static str {
+    if player.id == team.captain.id {
+        "captained"
+    } else {
+        "vice-captained"
+    }
+}
+
+pub fn team_captains_under_price_limit(
+    team: &Team,
+    price_limit_in_millions: f64,
+    include_vice_captain: bool,
+) -> ValidationResult {
+    let offending_players: Vec<Player> = captains(team, include_vice_captain)
+        .into_iter()
+        .filter(|player| player.price_in_millions >= price_limit_in_millions)
+        .cloned()
+        .collect();
+    if offending_players.is_empty() {
+        return ValidationResult::valid(team);
+    }
+
+    let armbands: Vec<String> = offending_players
+        .iter()
+        .map(|player| {
+            format!(
+                "{} {} ({}m)",
+                armband(team, player),
+                player.name,
+                player.price_in_millions
+            )
+        })
+        .collect();
+    let is_captain_triple_captained = is_captain_triple_captained(team, &offending_players);
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has {}, but captains must cost less than {}m",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            armbands.join(" and "),
+            price_limit_in_millions
+        ),
+    )
+    .with_offending_players(offending_players)
+    .with_chip_penalised_players(usize::from(is_captain_triple_captained))
+}
+
+pub fn team_captains_from_clubs(
+    clubs_by_club_id: &HashMap<i64, Club>,
+    club_ids: &[i64],
+    team: &Team,
+    include_vice_captain: bool,
+) -> ValidationResult {
+    let offending_players: Vec<Player> = captains(team, include_vice_captain)
+        .into_iter()
+        .filter(|player| !club_ids.contains(&player.club.id))
+        .cloned()
+        .collect();
+    if offending_players.is_empty() {
+        return ValidationResult::valid(team);
+    }
+
+    let armbands: Vec<String> = offending_players
+        .iter()
+        .map(|player| format!("{} {}", armband(team, player), player.name))
+        .collect();
+    let club_names: Vec<String> = club_ids
+        .iter()
+        .map(|club_id| {
+            clubs_by_club_id
+                .get(club_id)
+                .map(|club| club.name.clone())
+                .unwrap_or_else(|| format!("club {}", club_id))
+        })
+        .collect();
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has {}, but captains must play for {}",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            armbands.join(" and "),
+            club_names.join(", ")
+        ),
+    )
+    .with_offending_players(offending_players)
+}
+
+pub fn team_does_not_repeat_captain(team: &Team) -> ValidationResult {
+    if team.previous_captain_id != Some(team.captain.id) {
+        return ValidationResult::valid(team);
+    }
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has captained {} two gameweeks running",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            team.captain.name
+        ),
+    )
+    .with_offending_players(vec![team.captain.clone()])
 }
 
-pub fn run_validators_and_retain_violations(
+pub fn team_takes_hits_within_limit(team: &Team, max_hits: i64) -> ValidationResult {
+    let points_cost = team.gameweek_transfers.points_cost;
+    let hits = points_cost / POINTS_PER_HIT;
+    if hits <= max_hits {
+        return ValidationResult::valid(team);
+    }
+
+    let players_bought: Vec<Player> = team
+        .gameweek_transfers
+        .transfers
+        .iter()
+        .map(|transfer| transfer.player_in.clone())
+        .collect();
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has taken {} hits (-{}) this gameweek, but no more than {} {} allowed",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            hits,
+            points_cost,
+            max_hits,
+            if max_hits == 1 { "is" } else { "are" }
+        ),
+    )
+    .with_offending_players(players_bought)
+    .with_extra_players((hits - max_hits) as usize)
+}
+
+pub fn team_replaces_promoted_club_players(
     clubs_by_club_id: &HashMap<i64, Club>,
-    validation_results: &mut Vec<ValidationResult>,
+    club_ids: &[i64],
     team: &Team,
-) -> Vec<ValidationResult> {
-    validation_results.push(team_contains_players_under_10_m(team));
-    validation_results.push(team_contains_players_from_newly_promoted_clubs(
-        clubs_by_club_id,
+) -> ValidationResult {
+    let offending_transfers: Vec<&Transfer> = team
+        .gameweek_transfers
+        .transfers
+        .iter()
+        .filter(|transfer| {
+            club_ids.contains(&transfer.player_out.club.id)
+                && !club_ids.contains(&transfer.player_in.club.id)
+        })
+        .collect();
+    if offending_transfers.is_empty() {
+        return ValidationResult::valid(team);
+    }
+
+    let club_name = |player: &Player| {
+        clubs_by_club_id
+            .get(&player.club.id)
+            .map(|club| club.name.clone())
+            .unwrap_or_else(|| player.club.name.clone())
+    };
+    let sales: Vec<String> = offending_transfers
+        .iter()
+        .map(|transfer| {
+            format!(
+                "{} ({}) for {} ({})",
+                transfer.player_out.name,
+                club_name(&transfer.player_out),
+                transfer.player_in.name,
+                club_name(&transfer.player_in)
+            )
+        })
+        .collect();
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has sold {} without replacing them from a promoted club",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            sales.join(" and ")
+        ),
+    )
+    .with_offending_players(
+        offending_transfers
+            .iter()
+            .map(|transfer| transfer.player_out.clone())
+            .collect(),
+    )
+}
+
+pub fn team_squad_value_within_limit(team: &Team, max_value_in_millions: f64) -> ValidationResult {
+    // Teams built without picks (e.g. from a saved report) have no value to judge.
+    let Some(squad_value) = team.squad_value_in_millions() else {
+        return ValidationResult::valid(team);
+    };
+    if squad_value <= max_value_in_millions {
+        return ValidationResult::valid(team);
+    }
+
+    ValidationResult::invalid(
+        team,
+        &format!(
+            "{} {} has a squad worth {:.1}m, but squads may be worth at most {:.1}m",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            squad_value,
+            max_value_in_millions
+        ),
+    )
+}
+
+pub fn team_keeps_minimum_bank(team: &Team, min_bank_in_millions: f64) -> ValidationResult {
+    let Some(bank) = team.bank_in_millions else {
+        return ValidationResult::valid(team);
+    };
+    if bank >= min_bank_in_millions {
+        return ValidationResult::valid(team);
+    }
+
+    ValidationResult::invalid(
         team,
-    ));
-    validation_results.push(team_contains_at_most_one_player_per_club(team));
+        &format!(
+            "{} {} has only {:.1}m in the bank, but must keep at least {:.1}m",
+            VIOLATION_PREFIXES
+                .choose(&mut rand::rng())
+                .expect("Something went wrong grabbing a prefix"),
+            team.owner,
+            bank,
+            min_bank_in_millions
+        ),
+    )
+}
+
+pub fn team_contains_players_within_position_limit(
+    team: &Team,
+    position: Position,
+    min_price_in_millions: Option<f64>,
+    min_players: usize,
+    max_players: Option<usize>,
+) -> ValidationResult {
+    let matching_players: Vec<Player> = team
+        .players
+        .iter()
+        .filter(|player| player.position == Some(position))
+        .filter(|player| {
+            min_price_in_millions.is_none_or(|min_price| player.price_in_millions >= min_price)
+        })
+        .cloned()
+        .collect();
+    let prefix = VIOLATION_PREFIXES
+        .choose(&mut rand::rng())
+        .expect("Something went wrong grabbing a prefix");
+
+    if let Some(max_players) = max_players
+        && matching_players.len() > max_players
+    {
+        let player_list: Vec<String> = matching_players
+            .iter()
+            .map(|player| format!("{} ({}m)", player.name, player.price_in_millions))
+            .collect();
+        let extra_players = matching_players.len() - max_players;
+
+        return ValidationResult::invalid(
+            team,
+            &format!(
+                "{} {} has more than {}: {}",
+                prefix,
+                team.owner,
+                count_players(position, min_price_in_millions, max_players),
+                player_list.join(", ")
+            ),
+        )
+        .with_offending_players(matching_players)
+        .with_extra_players(extra_players);
+    }
+
+    if matching_players.len() < min_players {
+        return ValidationResult::invalid(
+            team,
+            &format!(
+                "{} {} has fewer than {} (only {})",
+                prefix,
+                team.owner,
+                count_players(position, min_price_in_millions, min_players),
+                matching_players.len()
+            ),
+        );
+    }
+
+    ValidationResult::valid(team)
+}
+
+/// Words `count` players of `position`, e.g. "1 forward costing 8m or more" or "5 defenders".
+fn count_players(position: Position, min_price_in_millions: Option<f64>, count: usize) -> String {
+    format!(
+        "{} {}{}",
+        count,
+        position.name_for(count),
+        min_price_in_millions
+            .map(|min_price| format!(" costing {}m or more", min_price))
+            .unwrap_or_default()
+    )
+}
+
+pub fn team_fields_legal_formation(
+    team: &Team,
+    play_limits_by_position: &IndexMap<Position, PlayLimits>,
+) -> ValidationResult {
+    let mut problems: Vec<String> = Vec::new();
+    let mut offending_players: Vec<Player> = Vec::new();
+
+    for (position, limits) in play_limits_by_position {
+        let players: Vec<&Player> = team
+            .players
+            .iter()
+            .filter(|player| player.position == Some(*position))
+            .collect();
+
+        if players.len() < limits.min {
+            problems.push(format!(
+                "{} {} (at least {})",
+                players.len(),
+                position.name_for(players.len()),
+                limits.min
+            ));
+        } else if players.len() > limits.max {
+            problems.push(format!(
+                "{} {} (at most {})",
+                players.len(),
+                position.name_for(players.len()),
+                limits.max
+            ));
+            offending_players.extend(players.into_iter().cloned());
+        }
+    }
+
+    if !problems.is_empty() {
+        return ValidationResult::invalid(
+            team,
+            &format!(
+                "{} {} has fielded an illegal formation with {}",
+                VIOLATION_PREFIXES
+                    .choose(&mut rand::rng())
+                    .expect("Something went wrong grabbing a prefix"),
+                team.owner,
+                problems.join(" and ")
+            ),
+        )
+        .with_offending_players(offending_players);
+    }
+
+    ValidationResult::valid(team)
+}
+
+pub struct PriceLimit {
+    pub price_limit_in_millions: f64,
+    pub price_basis: PriceBasis,
+    pub exempt_chips: Vec<Chip>,
+    pub scope: RuleScope,
+}
 
-    validation_results.retain(|result| !result.is_valid);
+impl Rule for PriceLimit {
+    fn id(&self) -> &str {
+        "price_limit"
+    }
+
+    fn description(&self) -> String {
+        let basis = match self.price_basis {
+            PriceBasis::Current => "",
+            PriceBasis::Purchase => " when bought",
+            PriceBasis::Selling => " to sell",
+        };
+
+        format!(
+            "No players costing {}m or more{}",
+            self.price_limit_in_millions, basis
+        )
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_contains_players_under_price_limit(
+            team,
+            self.price_limit_in_millions,
+            self.price_basis,
+        )
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
 
-    validation_results.clone()
+    fn scope(&self) -> RuleScope {
+        self.scope
+    }
+
+    fn needs_transfers(&self) -> bool {
+        self.price_basis != PriceBasis::Current
+    }
+}
+
+pub struct ClubLimit {
+    pub max_players_per_club: usize,
+    pub exempt_chips: Vec<Chip>,
+    pub scope: RuleScope,
+}
+
+impl Rule for ClubLimit {
+    fn id(&self) -> &str {
+        "club_limit"
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Maximum {} player(s) per Premier League club",
+            self.max_players_per_club
+        )
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_contains_at_most_n_players_per_club(team, self.max_players_per_club)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn scope(&self) -> RuleScope {
+        self.scope
+    }
+}
+
+pub struct PromotedClubs {
+    pub club_ids: Vec<i64>,
+    pub exempt_chips: Vec<Chip>,
+    pub scope: RuleScope,
+}
+
+impl Rule for PromotedClubs {
+    fn id(&self) -> &str {
+        "promoted_clubs"
+    }
+
+    fn description(&self) -> String {
+        "Must include players from every newly promoted club".to_string()
+    }
+
+    fn evaluate(&self, team: &Team, context: &RuleContext) -> ValidationResult {
+        team_contains_players_from_newly_promoted_clubs(
+            context.clubs_by_club_id,
+            &self.club_ids,
+            team,
+        )
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn scope(&self) -> RuleScope {
+        self.scope
+    }
+}
+
+pub struct CaptainPriceLimit {
+    pub price_limit_in_millions: f64,
+    pub include_vice_captain: bool,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for CaptainPriceLimit {
+    fn id(&self) -> &str {
+        "captain_price_limit"
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Captain{} must cost less than {}m",
+            if self.include_vice_captain {
+                " and vice-captain"
+            } else {
+                ""
+            },
+            self.price_limit_in_millions
+        )
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_captains_under_price_limit(
+            team,
+            self.price_limit_in_millions,
+            self.include_vice_captain,
+        )
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+}
+
+pub struct CaptainClubs {
+    pub club_ids: Vec<i64>,
+    pub include_vice_captain: bool,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for CaptainClubs {
+    fn id(&self) -> &str {
+        "captain_clubs"
+    }
+
+    fn description(&self) -> String {
+        let club_ids: Vec<String> = self.club_ids.iter().map(i64::to_string).collect();
+
+        format!(
+            "Captain{} must play for club {}",
+            if self.include_vice_captain {
+                " and vice-captain"
+            } else {
+                ""
+            },
+            club_ids.join(", ")
+        )
+    }
+
+    fn evaluate(&self, team: &Team, context: &RuleContext) -> ValidationResult {
+        team_captains_from_clubs(
+            context.clubs_by_club_id,
+            &self.club_ids,
+            team,
+            self.include_vice_captain,
+        )
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+}
+
+pub struct RepeatCaptain {
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for RepeatCaptain {
+    fn id(&self) -> &str {
+        "repeat_captain"
+    }
+
+    fn description(&self) -> String {
+        "No captaining the same player in consecutive gameweeks".to_string()
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_does_not_repeat_captain(team)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn needs_previous_captain(&self) -> bool {
+        true
+    }
+}
+
+pub struct PositionLimit {
+    pub id: String,
+    pub position: Position,
+    pub min_price_in_millions: Option<f64>,
+    pub min_players: usize,
+    pub max_players: Option<usize>,
+    pub exempt_chips: Vec<Chip>,
+    pub scope: RuleScope,
+}
+
+impl Rule for PositionLimit {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> String {
+        match self.max_players {
+            Some(max_players) if self.min_players > 0 => format!(
+                "Between {} and {}",
+                self.min_players,
+                count_players(self.position, self.min_price_in_millions, max_players)
+            ),
+            Some(max_players) => format!(
+                "At most {}",
+                count_players(self.position, self.min_price_in_millions, max_players)
+            ),
+            None => format!(
+                "At least {}",
+                count_players(self.position, self.min_price_in_millions, self.min_players)
+            ),
+        }
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_contains_players_within_position_limit(
+            team,
+            self.position,
+            self.min_price_in_millions,
+            self.min_players,
+            self.max_players,
+        )
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn scope(&self) -> RuleScope {
+        self.scope
+    }
+}
+
+pub struct Formation {
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for Formation {
+    fn id(&self) -> &str {
+        "formation"
+    }
+
+    fn description(&self) -> String {
+        "Starting XI must field a legal formation".to_string()
+    }
+
+    fn evaluate(&self, team: &Team, context: &RuleContext) -> ValidationResult {
+        team_fields_legal_formation(team, context.play_limits_by_position)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn scope(&self) -> RuleScope {
+        RuleScope::StartingXi
+    }
+}
+
+pub struct HitLimit {
+    pub max_hits: i64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for HitLimit {
+    fn id(&self) -> &str {
+        "hit_limit"
+    }
+
+    fn description(&self) -> String {
+        format!("No more than {} -4 hits per gameweek", self.max_hits)
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_takes_hits_within_limit(team, self.max_hits)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn needs_transfers(&self) -> bool {
+        true
+    }
+}
+
+pub struct PromotedClubTransfers {
+    pub club_ids: Vec<i64>,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for PromotedClubTransfers {
+    fn id(&self) -> &str {
+        "promoted_club_transfers"
+    }
+
+    fn description(&self) -> String {
+        let club_ids: Vec<String> = self.club_ids.iter().map(i64::to_string).collect();
+
+        format!(
+            "Players sold from club {} must be replaced from one of those clubs",
+            club_ids.join(", ")
+        )
+    }
+
+    fn evaluate(&self, team: &Team, context: &RuleContext) -> ValidationResult {
+        team_replaces_promoted_club_players(context.clubs_by_club_id, &self.club_ids, team)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+
+    fn needs_transfers(&self) -> bool {
+        true
+    }
+}
+
+pub struct SquadValueLimit {
+    pub max_value_in_millions: f64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for SquadValueLimit {
+    fn id(&self) -> &str {
+        "squad_value_limit"
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Squad may be worth at most {}m, not counting the bank",
+            self.max_value_in_millions
+        )
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_squad_value_within_limit(team, self.max_value_in_millions)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+}
+
+pub struct MinimumBank {
+    pub min_bank_in_millions: f64,
+    pub exempt_chips: Vec<Chip>,
+}
+
+impl Rule for MinimumBank {
+    fn id(&self) -> &str {
+        "minimum_bank"
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "At least {}m must be kept in the bank",
+            self.min_bank_in_millions
+        )
+    }
+
+    fn evaluate(&self, team: &Team, _context: &RuleContext) -> ValidationResult {
+        team_keeps_minimum_bank(team, self.min_bank_in_millions)
+    }
+
+    fn exempt_chips(&self) -> &[Chip] {
+        &self.exempt_chips
+    }
+}
+
+pub fn run_validators(registry: &RuleRegistry, context: &RuleContext, team: &Team) -> TeamReport {
+    TeamReport {
+        team: team.clone(),
+        results: registry.evaluate(team, context),
+        health_check: Vec::new(),
+    }
 }